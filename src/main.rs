@@ -18,7 +18,13 @@ mod plugins;
 mod state;
 
 const PIXELS_PER_METER: f32 = 1.0;
-const GRAVITY: Vec2 = Vec2::new(0.0, -9.81);
+/// The fastest anything can fall, in pixels per second.
+const GRAVITY: Vec2 = Vec2::new(0.0, -588.6);
+/// How quickly falling things speed up, in pixels per second per second.
+const FALL_ACCELERATION: f32 = 2400.0;
+/// The default physics tick rate, can be changed with `POLLYWOG_PHYSICS_FRAMERATE`.
+///
+/// All motion is in units per second, so this doesn't change how the game plays.
 const PHYSICS_FRAMERATE: f64 = 60.0;
 
 const BACKGROUND_COLOR: Color = Color::Rgba {
@@ -29,12 +35,19 @@ const BACKGROUND_COLOR: Color = Color::Rgba {
 };
 
 pub fn main() {
+    if std::env::args().any(|arg| arg == plugins::in_game::assets::manifest::CHECK_ASSETS_FLAG) {
+        let all_found = plugins::in_game::assets::manifest::run_cli_check();
+        std::process::exit(if all_found { 0 } else { 1 });
+    }
+
     eprintln!("Starting pollywog...");
 
     App::new()
         .init_state::<GameState>()
         .insert_resource(ClearColor(BACKGROUND_COLOR))
-        .insert_resource(Time::<Fixed>::from_seconds(1.0 / PHYSICS_FRAMERATE))
+        .insert_resource(Time::<Fixed>::from_seconds(1.0 / physics_framerate()))
+        // Must be before `DefaultPlugins` so the asset source is registered in time
+        .add_plugins(plugins::in_game::level::packs::packs_source_plugin)
         .add_plugins((
             setup_default_plugins(),
             perf_ui_plugin,
@@ -45,10 +58,32 @@ pub fn main() {
             plugins::camera::camera_plugin,
             plugins::screens_plugin,
         ))
+        .add_systems(Startup, configure_physics)
         .add_systems(Update, bevy::window::close_on_esc)
         .run();
 }
 
+/// Makes dynamic bodies (eg crates) fall the same way characters do.
+fn configure_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.gravity = Vec2::new(0.0, -FALL_ACCELERATION);
+}
+
+fn physics_framerate() -> f64 {
+    match std::env::var("POLLYWOG_PHYSICS_FRAMERATE") {
+        Ok(framerate) => match framerate.parse::<f64>() {
+            Ok(framerate) if framerate > 0.0 => framerate,
+            _ => {
+                eprintln!(
+                    "Invalid POLLYWOG_PHYSICS_FRAMERATE `{}`, using {}",
+                    framerate, PHYSICS_FRAMERATE
+                );
+                PHYSICS_FRAMERATE
+            }
+        },
+        Err(_) => PHYSICS_FRAMERATE,
+    }
+}
+
 fn setup_default_plugins() -> PluginGroupBuilder {
     DefaultPlugins
         .set(LogPlugin {
@@ -99,4 +134,6 @@ pub mod z_index {
     pub const SWAMP_POND: ZIndex = BG_BASE + 8.0;
 
     pub const LEVEL_BASE: ZIndex = BG_MAX + 1.0;
+    /// The grapple's rope, marker and aim preview.
+    pub const GRAPPLE: ZIndex = LEVEL_BASE + 1.0;
 }
@@ -1,15 +1,32 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::plugins::in_game::{player::Player, InGameSet};
+use crate::plugins::in_game::{
+    components::interpolated::InterpolationSet, level::LevelBounds, player::Player, InGameSet,
+};
 
-const MAX_SPEED_X: f32 = 3.0;
-const MAX_SPEED_Y: f32 = 1.0;
+/// In pixels per second
+const MAX_SPEED_X: f32 = 180.0;
+/// In pixels per second
+const MAX_SPEED_Y: f32 = 60.0;
+/// How much of the distance to the player the camera covers each second.
+const FOLLOW_RATE: f32 = 0.45;
+/// How far the player can get from the center before the camera moves, in
+/// pixels.
+const DEAD_ZONE: f32 = 25.0;
+/// How much room to leave between the players and the edge of the screen when
+/// zooming out to fit them all in, in pixels.
+const FRAMING_MARGIN: f32 = 160.0;
+/// The furthest the camera zooms out to fit every player in.
+const MAX_ZOOM: f32 = 2.0;
+/// How much of the difference in zoom the camera covers each second.
+const ZOOM_RATE: f32 = 0.6;
 
 pub fn camera_plugin(app: &mut App) {
     app.add_systems(Startup, spawn_camera).add_systems(
         Update,
-        keep_player_in_view
-            .after(crate::plugins::in_game::components::character::r#move)
+        keep_players_in_view
+            // Follow where the players are drawn, not where physics has them
+            .after(InterpolationSet)
             .in_set(CameraSet)
             .in_set(InGameSet),
     );
@@ -29,26 +46,97 @@ pub fn spawn_camera(mut commands: Commands) {
     });
 }
 
-pub fn keep_player_in_view(
-    mut camera_query: Query<&mut Transform, With<Camera>>,
+/// Follows the middle of all of the players, and zooms out if they wouldn't
+/// all fit on screen.
+pub fn keep_players_in_view(
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
     // The `Without<Camera>` allows us to have seperate queries for the player
     // and the camera (needed b/c they are both accessing `Transform`, and if
     // there somehow was a Transform w/ both `Player` and `Camera` components,
     // it wouldn't be allowed. we know that it isn't possible, but bevy doesn't)
     player_query: Query<&Transform, (With<Player>, Without<Camera>)>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    level_bounds: Option<Res<LevelBounds>>,
 ) {
-    let mut camera = camera_query.single_mut();
-    let Ok(player) = player_query.get_single() else {
+    let (mut camera, mut projection) = camera_query.single_mut();
+    let mut players = player_query
+        .iter()
+        .map(|player| player.translation.truncate());
+    let Some(first) = players.next() else {
         return;
     };
+    let (min, max) = players.fold((first, first), |(min, max), player| {
+        (min.min(player), max.max(player))
+    });
+    let center = (min + max) / 2.0;
+
+    let delta_seconds = time.delta_seconds();
+    follow(
+        center.x,
+        &mut camera.translation.x,
+        MAX_SPEED_X * delta_seconds,
+        delta_seconds,
+    );
+    follow(
+        center.y,
+        &mut camera.translation.y,
+        MAX_SPEED_Y * delta_seconds,
+        delta_seconds,
+    );
+
+    let window = window_query.single();
+    let window_size = Vec2::new(window.width(), window.height());
+    let zoom = ((max - min + Vec2::splat(FRAMING_MARGIN * 2.0)) / window_size)
+        .max_element()
+        .clamp(1.0, MAX_ZOOM);
+    if zoom != projection.scale {
+        let rate = 1.0 - (1.0 - ZOOM_RATE).powf(delta_seconds);
+        projection.scale += (zoom - projection.scale) * rate;
+    }
+
+    if let Some(level_bounds) = level_bounds {
+        let viewport_size = window_size * projection.scale;
+        let position =
+            clamp_to_bounds(camera.translation.truncate(), viewport_size, level_bounds.0);
+        camera.translation.x = position.x;
+        camera.translation.y = position.y;
+    }
+}
+
+/// Moves the camera `position` so that a viewport of `viewport_size` centered
+/// on it stays inside of `bounds`.
+///
+/// If the level is narrower than the viewport it is centered, and if it's
+/// shorter it's aligned to the bottom so the void is above the level instead
+/// of below the floor.
+fn clamp_to_bounds(position: Vec2, viewport_size: Vec2, bounds: Rect) -> Vec2 {
+    let half_viewport = viewport_size / 2.0;
+    let min = bounds.min + half_viewport;
+    let max = bounds.max - half_viewport;
+
+    let x = if min.x <= max.x {
+        position.x.clamp(min.x, max.x)
+    } else {
+        bounds.center().x
+    };
+    let y = if min.y <= max.y {
+        position.y.clamp(min.y, max.y)
+    } else {
+        min.y
+    };
 
-    follow(player.translation.x, &mut camera.translation.x, MAX_SPEED_X);
-    follow(player.translation.y, &mut camera.translation.y, MAX_SPEED_Y);
+    Vec2::new(x, y)
 }
 
-fn follow(a: f32, b: &mut f32, max_speed: f32) {
+/// Moves `b` towards `a`, by at most `max_delta`.
+///
+/// Eases out the same way no matter the frame rate.
+fn follow(a: f32, b: &mut f32, max_delta: f32, delta_seconds: f32) {
     let delta = *b - a;
-    let delta = delta / 100.0;
-    let delta = if delta.abs() < 0.25 { 0.0 } else { delta };
-    *b -= delta.min(max_speed);
+    if delta.abs() < DEAD_ZONE {
+        return;
+    }
+    let delta = delta * (1.0 - (1.0 - FOLLOW_RATE).powf(delta_seconds));
+    *b -= delta.clamp(-max_delta, max_delta);
 }
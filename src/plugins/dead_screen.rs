@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use crate::plugins::in_game::level::{LevelList, SelectLevel};
 use crate::state::GameState;
 
 pub fn dead_screen_plugin(app: &mut App) {
@@ -41,7 +42,7 @@ fn setup(mut commands: Commands) {
         .with_children(|parent| {
             parent.spawn(TextBundle {
                 text: Text::from_section(
-                    "You died. Sorry!",
+                    "You died. Sorry!\nGame over.",
                     TextStyle {
                         font_size: 100.0,
                         color: Color::WHITE,
@@ -61,7 +62,7 @@ fn setup(mut commands: Commands) {
                 .with_children(|parent| {
                     parent.spawn(TextBundle {
                         text: Text::from_section(
-                            "Restart",
+                            "Restart Campaign",
                             TextStyle {
                                 font_size: 42.0,
                                 color: Color::BLACK,
@@ -102,13 +103,22 @@ fn cleanup(mut commands: Commands, dead_screen_root_node: Query<Entity, With<Roo
     commands.entity(root_node).despawn_recursive();
 }
 
+/// Starts again from the first level, with all of its lives.
 fn restart_button_pressed(
-    mut next_state: ResMut<NextState<GameState>>,
-    mut interaction_query: Query<(&Interaction, &RestartButton)>,
+    level_list: Res<LevelList>,
+    mut select_level: EventWriter<SelectLevel>,
+    interaction_query: Query<(&Interaction, &RestartButton), Changed<Interaction>>,
 ) {
-    for (interaction, _button) in &mut interaction_query {
+    for (interaction, _button) in &interaction_query {
         match *interaction {
-            Interaction::Pressed => next_state.set(GameState::InGame),
+            Interaction::Pressed => {
+                let Some(first_level) = level_list.0.first() else {
+                    warn!("No levels to restart the campaign with");
+                    continue;
+                };
+                info!("Restarting campaign");
+                select_level.send(SelectLevel(first_level.clone()));
+            }
             Interaction::Hovered | Interaction::None => {}
         }
     }
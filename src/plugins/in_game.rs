@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 use crate::state::GameState;
 
+pub mod assets;
 mod bundles;
 pub mod components;
 pub mod level;
@@ -11,26 +12,31 @@ pub fn in_game_plugin(app: &mut App) {
     let in_game_set_config = || InGameSet.run_if(in_state(GameState::InGame));
 
     app.add_plugins((
+        assets::assets_plugin,
         player::player_plugin,
         level::level_plugin,
         components::ComponentsPlugin,
     ))
     .configure_sets(Update, in_game_set_config())
+    .configure_sets(FixedFirst, in_game_set_config())
     .configure_sets(FixedUpdate, in_game_set_config())
+    .configure_sets(FixedLast, in_game_set_config())
     // Exit after `Win`/`Dead` so there can be a screenshot of the game in the background
     .add_systems(OnExit(GameState::Win), cleanup)
-    .add_systems(OnExit(GameState::Dead), cleanup);
+    .add_systems(OnExit(GameState::Dead), cleanup)
+    // Respawning rebuilds the level straight away, without a screen
+    .add_systems(OnEnter(GameState::Respawning), respawn)
+    .add_systems(OnExit(GameState::Respawning), cleanup);
 }
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InGameSet;
 
-// TODO: clean this up so it doesn't need to import `components::character::grapple::GrappleState`
-fn cleanup(
-    commands: Commands,
-    query: Query<Entity, With<level::LevelEntity>>,
-    next_grapple_state: ResMut<NextState<components::character::grapple::GrappleState>>,
-) {
-    components::cleanup(next_grapple_state);
+fn respawn(mut next_state: ResMut<NextState<GameState>>) {
+    info!("Respawning players");
+    next_state.set(GameState::InGame);
+}
+
+fn cleanup(commands: Commands, query: Query<Entity, With<level::LevelEntity>>) {
     level::despawn_entities(commands, query);
 }
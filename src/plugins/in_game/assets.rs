@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetId, LoadState, UntypedAssetId},
+    prelude::*,
+};
+
+use crate::plugins::in_game::{
+    bundles::{background::BackgroundSection, coin, enemy},
+    components::character::grapple::visuals,
+    player,
+};
+
+use self::atlas::AtlasDescriptor;
+
+pub mod atlas;
+pub mod manifest;
+
+pub fn assets_plugin(app: &mut App) {
+    app.init_state::<AssetsState>()
+        .add_plugins(atlas::atlas_plugin)
+        .add_systems(Startup, (manifest::check_manifest_on_startup, load_assets))
+        .add_systems(
+            Update,
+            check_assets_loaded.run_if(in_state(AssetsState::Loading)),
+        )
+        .add_systems(OnEnter(AssetsState::Loaded), build_atlas_layouts);
+}
+
+/// Whether all of the asset collections have finished loading.
+///
+/// Nothing that needs the assets (ie the level or the player) should run
+/// until this is `Loaded`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, States)]
+pub enum AssetsState {
+    #[default]
+    Loading,
+    Loaded,
+    Failed,
+}
+
+/// Every texture used by the game.
+#[derive(Resource)]
+pub struct ImageAssets {
+    pub player: Handle<Image>,
+    pub enemy: Handle<Image>,
+    pub coin: Handle<Image>,
+    pub rope: Handle<Image>,
+    pub backgrounds: HashMap<BackgroundSection, Handle<Image>>,
+    /// The default path of every overridden texture, by the id of the
+    /// override.
+    defaults: HashMap<AssetId<Image>, String>,
+}
+
+impl ImageAssets {
+    /// Loads every texture, using the path in `overrides` instead of the
+    /// default one if there is an entry for it.
+    pub fn load(asset_server: &AssetServer, overrides: &HashMap<String, String>) -> Self {
+        let mut defaults = HashMap::new();
+        let mut load = |path: String| -> Handle<Image> {
+            match overrides.get(&path) {
+                Some(override_path) => {
+                    debug!("Overriding texture `{}` with `{}`", path, override_path);
+                    let handle = asset_server.load(override_path.clone());
+                    defaults.insert(handle.id(), path);
+                    handle
+                }
+                None => asset_server.load(path),
+            }
+        };
+
+        let player = load(player::TEXTURE_PATH.to_string());
+        let enemy = load(enemy::TEXTURE_PATH.to_string());
+        let coin = load(coin::TEXTURE_PATH.to_string());
+        let rope = load(visuals::ROPE_TEXTURE_PATH.to_string());
+        let backgrounds = BackgroundSection::enumerate()
+            .into_iter()
+            .map(|section| (section, load(section.texture_path())))
+            .collect();
+
+        ImageAssets {
+            player,
+            enemy,
+            coin,
+            rope,
+            backgrounds,
+            defaults,
+        }
+    }
+
+    /// Swaps an override that failed to load for the default texture.
+    ///
+    /// Does nothing if `failed` isn't an override, since there is nothing to
+    /// fall back to.
+    fn fall_back(&mut self, failed: AssetId<Image>, asset_server: &AssetServer) {
+        let Some(default_path) = self.defaults.remove(&failed) else {
+            return;
+        };
+
+        warn!(
+            "Texture override {:?} failed to load, falling back to `{}`",
+            asset_server.get_path(failed),
+            default_path
+        );
+        let default: Handle<Image> = asset_server.load(default_path);
+        for handle in self.iter_mut() {
+            if handle.id() == failed {
+                *handle = default.clone();
+            }
+        }
+    }
+
+    pub fn background(&self, section: BackgroundSection) -> Handle<Image> {
+        self.backgrounds
+            .get(&section)
+            .cloned()
+            .expect("every background section is in the image assets")
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Handle<Image>> {
+        [&self.player, &self.enemy, &self.coin, &self.rope]
+            .into_iter()
+            .chain(self.backgrounds.values())
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut Handle<Image>> {
+        [
+            &mut self.player,
+            &mut self.enemy,
+            &mut self.coin,
+            &mut self.rope,
+        ]
+        .into_iter()
+        .chain(self.backgrounds.values_mut())
+    }
+}
+
+/// The descriptors for every animated texture atlas.
+#[derive(Resource)]
+pub struct AtlasDescriptors {
+    pub player: Handle<AtlasDescriptor>,
+    pub coin: Handle<AtlasDescriptor>,
+}
+
+impl AtlasDescriptors {
+    fn iter(&self) -> impl Iterator<Item = &Handle<AtlasDescriptor>> {
+        [&self.player, &self.coin].into_iter()
+    }
+}
+
+/// Every texture atlas layout used by the game.
+///
+/// These are built from the `AtlasDescriptors` once they have loaded.
+#[derive(Resource)]
+pub struct AtlasLayouts {
+    pub player: Handle<TextureAtlasLayout>,
+    pub coin: Handle<TextureAtlasLayout>,
+}
+
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    info!("Loading game assets");
+
+    commands.insert_resource(ImageAssets::load(&asset_server, &HashMap::new()));
+    commands.insert_resource(AtlasDescriptors {
+        player: asset_server.load(player::ATLAS_PATH),
+        coin: asset_server.load(coin::ATLAS_PATH),
+    });
+}
+
+fn build_atlas_layouts(
+    mut commands: Commands,
+    atlas_descriptors: Res<AtlasDescriptors>,
+    descriptors: Res<Assets<AtlasDescriptor>>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let mut layout = |handle: &Handle<AtlasDescriptor>| {
+        let descriptor = descriptors
+            .get(handle)
+            .expect("atlas descriptors are loaded before the layouts are built");
+        texture_atlas_layouts.add(descriptor.layout())
+    };
+
+    debug!("Building atlas layouts");
+    commands.insert_resource(AtlasLayouts {
+        player: layout(&atlas_descriptors.player),
+        coin: layout(&atlas_descriptors.coin),
+    });
+}
+
+fn check_assets_loaded(
+    asset_server: Res<AssetServer>,
+    mut image_assets: ResMut<ImageAssets>,
+    atlas_descriptors: Res<AtlasDescriptors>,
+    mut next_state: ResMut<NextState<AssetsState>>,
+) {
+    // Level packs can override textures with files that are missing or
+    // broken, which shouldn't stop the level from loading
+    let failed_images: Vec<AssetId<Image>> = image_assets
+        .iter()
+        .map(|handle| handle.id())
+        .filter(|id| matches!(asset_server.get_load_state(*id), Some(LoadState::Failed)))
+        .collect();
+    for id in failed_images {
+        image_assets.fall_back(id, &asset_server);
+    }
+
+    let mut all_loaded = true;
+    let mut any_failed = false;
+
+    let ids = image_assets
+        .iter()
+        .map(|handle| handle.id().untyped())
+        .chain(atlas_descriptors.iter().map(|handle| handle.id().untyped()));
+    for id in ids {
+        match asset_server.get_load_state(id) {
+            Some(LoadState::Loaded) => {}
+            Some(LoadState::Failed) => {
+                any_failed = true;
+                log_failed(&asset_server, id);
+            }
+            _ => all_loaded = false,
+        }
+    }
+
+    if any_failed {
+        error!("Some assets failed to load, the game can't be started.");
+        next_state.set(AssetsState::Failed);
+    } else if all_loaded {
+        info!("Finished loading game assets");
+        next_state.set(AssetsState::Loaded);
+    }
+}
+
+fn log_failed(asset_server: &AssetServer, id: UntypedAssetId) {
+    match asset_server.get_path(id) {
+        Some(path) => error!("Failed to load asset `{}`", path),
+        None => error!("Failed to load asset {:?}", id),
+    }
+}
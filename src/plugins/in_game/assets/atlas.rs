@@ -0,0 +1,89 @@
+//! Texture atlas descriptors, which live next to the atlas PNG they describe
+//! (eg `coin.atlas.png` and `coin.atlas.json`):
+//!
+//! ```json
+//! {
+//!     "tile_size": [79.0, 79.0],
+//!     "columns": 7,
+//!     "rows": 2,
+//!     "default_clip": "spin",
+//!     "clips": {
+//!         "spin": {
+//!             "first": 0,
+//!             "last": 12,
+//!             "frame_duration": 0.05,
+//!             "frame_durations": { "0": 0.2 },
+//!             "mode": "loop",
+//!             "events": { "6": "shine" }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Frames are atlas indices, and `first`/`last` are both inclusive.
+//! `frame_durations` overrides `frame_duration` for single frames, and
+//! `events` sends an `AnimationEvent` when a frame is shown.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_common_assets::json::JsonAssetPlugin;
+
+pub fn atlas_plugin(app: &mut App) {
+    app.add_plugins(JsonAssetPlugin::<AtlasDescriptor>::new(&["atlas.json"]));
+}
+
+#[derive(Clone, Debug, serde::Deserialize, Asset, bevy::reflect::TypePath)]
+pub struct AtlasDescriptor {
+    /// The size of a single frame, in pixels.
+    tile_size: Vec2,
+    columns: usize,
+    rows: usize,
+    /// The clip that is played when nothing else has been asked for.
+    pub default_clip: String,
+    pub clips: HashMap<String, Clip>,
+}
+
+impl AtlasDescriptor {
+    pub fn layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(self.tile_size, self.columns, self.rows, None, None)
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct Clip {
+    pub first: usize,
+    pub last: usize,
+    /// How long each frame is shown for, in seconds.
+    pub frame_duration: f32,
+    /// Overrides `frame_duration` for some frames, keyed by atlas index.
+    #[serde(default)]
+    pub frame_durations: HashMap<usize, f32>,
+    #[serde(default)]
+    pub mode: LoopMode,
+    /// Events to send when a frame is shown, keyed by atlas index.
+    #[serde(default)]
+    pub events: HashMap<usize, String>,
+}
+
+impl Clip {
+    pub fn duration(&self, frame: usize) -> f32 {
+        self.frame_durations
+            .get(&frame)
+            .copied()
+            .unwrap_or(self.frame_duration)
+    }
+}
+
+/// What happens when a clip reaches its last frame.
+#[derive(Copy, Clone, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    /// Start again from the first frame.
+    #[default]
+    Loop,
+    /// Stay on the last frame.
+    Once,
+    /// Play backwards to the first frame, then forwards again.
+    PingPong,
+}
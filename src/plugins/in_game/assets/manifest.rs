@@ -0,0 +1,202 @@
+//! A list of every file the game loads from `assets/`, and a check that they
+//! all exist.
+//!
+//! The check is case-sensitive even on case-insensitive filesystems (macOS,
+//! Windows), so a path like `backgrounds/swamp/hills0.png` is caught before it
+//! silently fails to load on Linux.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::plugins::in_game::{
+    bundles::{background::BackgroundSection, coin, enemy},
+    components::character::grapple::visuals,
+    level, player,
+};
+
+/// The command line flag that runs the check and exits.
+pub const CHECK_ASSETS_FLAG: &str = "--check-assets";
+
+/// Every asset path (relative to the asset root) that the game references.
+pub fn manifest() -> Vec<String> {
+    let mut paths = vec![
+        player::TEXTURE_PATH.to_string(),
+        player::ATLAS_PATH.to_string(),
+        enemy::TEXTURE_PATH.to_string(),
+        coin::TEXTURE_PATH.to_string(),
+        coin::ATLAS_PATH.to_string(),
+        visuals::ROPE_TEXTURE_PATH.to_string(),
+    ];
+    paths.extend(
+        BackgroundSection::enumerate()
+            .iter()
+            .map(BackgroundSection::texture_path),
+    );
+    paths.extend(level::LEVEL_PATHS.iter().map(|path| path.to_string()));
+
+    paths
+}
+
+/// Mirrors how bevy's file asset reader finds the `assets` folder.
+pub fn asset_root() -> PathBuf {
+    let base = std::env::var("BEVY_ASSET_ROOT")
+        .or_else(|_| std::env::var("CARGO_MANIFEST_DIR"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf))
+                .unwrap_or_default()
+        });
+
+    base.join("assets")
+}
+
+#[derive(Debug)]
+pub struct MissingAsset {
+    pub path: String,
+    /// The closest existing path, if there is one that is close enough.
+    pub suggestion: Option<String>,
+}
+
+impl std::fmt::Display for MissingAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "missing asset `{}`", self.path)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that every path in the manifest exists in `root`, with exact casing.
+pub fn check(root: &Path) -> Vec<MissingAsset> {
+    manifest()
+        .into_iter()
+        .filter_map(|path| check_path(root, &path))
+        .collect()
+}
+
+/// Walks `path` one component at a time, so that a mismatch in a directory
+/// name is caught too. If a component doesn't exist, the closest entry in that
+/// directory is used for the rest of the walk to build a suggestion.
+fn check_path(root: &Path, path: &str) -> Option<MissingAsset> {
+    let mut dir = root.to_path_buf();
+    let mut suggestion = Vec::new();
+    let mut found = true;
+
+    for component in path.split('/') {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+
+        if entries.iter().any(|entry| entry == component) {
+            suggestion.push(component.to_string());
+            dir.push(component);
+            continue;
+        }
+
+        found = false;
+        match closest_match(component, &entries) {
+            Some(closest) => {
+                dir.push(&closest);
+                suggestion.push(closest);
+            }
+            None => {
+                return Some(MissingAsset {
+                    path: path.to_string(),
+                    suggestion: None,
+                })
+            }
+        }
+    }
+
+    (!found).then(|| MissingAsset {
+        path: path.to_string(),
+        suggestion: Some(suggestion.join("/")),
+    })
+}
+
+/// Finds the entry that is closest to `name`, preferring ones that only differ
+/// in case. Entries that are too different to be a typo are ignored.
+fn closest_match(name: &str, entries: &[String]) -> Option<String> {
+    let name_lower = name.to_lowercase();
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.to_lowercase() == name_lower)
+    {
+        return Some(entry.clone());
+    }
+
+    let max_distance = (name.len() / 3).max(1);
+    entries
+        .iter()
+        .map(|entry| (edit_distance(&name_lower, &entry.to_lowercase()), entry))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, entry)| entry.clone())
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Runs the check for the `--check-assets` flag, printing the results.
+///
+/// # Returns
+///
+/// Whether all of the assets were found.
+pub fn run_cli_check() -> bool {
+    let root = asset_root();
+    let missing = check(&root);
+
+    if missing.is_empty() {
+        eprintln!(
+            "All {} assets found in {}.",
+            manifest().len(),
+            root.display()
+        );
+        return true;
+    }
+
+    for missing in &missing {
+        eprintln!("error: {}", missing);
+    }
+    eprintln!(
+        "{} of {} assets are missing from {}.",
+        missing.len(),
+        manifest().len(),
+        root.display()
+    );
+    false
+}
+
+pub(super) fn check_manifest_on_startup() {
+    let root = asset_root();
+    let missing = check(&root);
+
+    for missing in &missing {
+        error!("{}", missing);
+    }
+    if missing.is_empty() {
+        debug!("All assets in the manifest were found in {:?}", root);
+    }
+}
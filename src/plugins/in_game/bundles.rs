@@ -1,3 +1,5 @@
 pub mod background;
+pub mod checkpoint;
 pub mod coin;
 pub mod enemy;
+pub mod power_up;
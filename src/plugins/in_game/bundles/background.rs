@@ -4,10 +4,7 @@
 use bevy::prelude::*;
 use serde::Deserialize;
 
-use crate::{
-    plugins::in_game::level::{ImageHandleId, ImageHandles},
-    BACKGROUND_COLOR,
-};
+use crate::BACKGROUND_COLOR;
 
 #[derive(Component)]
 pub struct Background {
@@ -35,7 +32,7 @@ pub struct BackgroundBundle {
 }
 
 impl BackgroundBundle {
-    pub fn new(section: BackgroundSection, image_handles: &ImageHandles) -> Self {
+    pub fn new(section: BackgroundSection, texture: Handle<Image>) -> Self {
         /// Takes the relative position of trans in the trans_range and
         /// maps it to the relative position in the total_range.
         fn map_transparency(total_range: (f32, f32), trans_range: (f32, f32), trans: f32) -> f32 {
@@ -57,7 +54,7 @@ impl BackgroundBundle {
                 transform: Transform::from_translation(
                     Vec2::new(0.0, section.size().y / 5.0).extend(section.z()),
                 ),
-                texture: image_handles.texture.clone(),
+                texture,
                 ..default()
             },
             background: Background { section },
@@ -93,7 +90,7 @@ impl Biome {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum BackgroundSection {
     SwampHills0,
     SwampHills1,
@@ -143,36 +140,19 @@ impl BackgroundSection {
         .into()
     }
 
-    pub fn image_handle_id(&self) -> ImageHandleId {
-        use BackgroundSection::*;
-        use ImageHandleId::*;
-
-        match &self {
-            SwampHills0 => BackgroundSwampHills0,
-            SwampHills1 => BackgroundSwampHills1,
-            SwampHills2 => BackgroundSwampHills2,
-            SwampIsland0 => BackgroundSwampIsland0,
-            SwampIsland1 => BackgroundSwampIsland1,
-            SwampIsland2 => BackgroundSwampIsland2,
-            SwampKelp0 => BackgroundSwampKelp0,
-            SwampKelp1 => BackgroundSwampKelp1,
-            SwampPond => BackgroundSwampPond,
-        }
-    }
-
     pub fn texture_path(&self) -> String {
         use BackgroundSection::*;
 
         "backgrounds/".to_string()
             + match &self {
-                SwampHills0 => "swamp/hills0.png",
-                SwampHills1 => "swamp/hills1.png",
-                SwampHills2 => "swamp/hills2.png",
-                SwampIsland0 => "swamp/island0.png",
-                SwampIsland1 => "swamp/island1.png",
-                SwampIsland2 => "swamp/island2.png",
-                SwampKelp0 => "swamp/kelp0.png",
-                SwampKelp1 => "swamp/kelp1.png",
+                SwampHills0 => "swamp/Hills0.png",
+                SwampHills1 => "swamp/Hills1.png",
+                SwampHills2 => "swamp/Hills2.png",
+                SwampIsland0 => "swamp/Island0.png",
+                SwampIsland1 => "swamp/Island1.png",
+                SwampIsland2 => "swamp/Island2.png",
+                SwampKelp0 => "swamp/Kelp0.png",
+                SwampKelp1 => "swamp/Kelp1.png",
                 SwampPond => "swamp/pond.png",
             }
     }
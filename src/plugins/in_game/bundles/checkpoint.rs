@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::z_index;
+
+/// In pixels
+const SIZE: Vec2 = Vec2::new(16.0, 96.0);
+const COLOR: Color = Color::rgb(0.85, 0.85, 0.85);
+
+/// Touching this makes the player respawn here after dying.
+#[derive(Component, Default)]
+pub struct Checkpoint;
+
+#[derive(Bundle)]
+pub struct CheckpointBundle {
+    collider: Collider,
+    sensor: Sensor,
+    active_collision_types: ActiveCollisionTypes,
+    sprite_bundle: SpriteBundle,
+    checkpoint: Checkpoint,
+}
+
+impl CheckpointBundle {
+    pub fn new(translation: Vec2) -> Self {
+        trace!(
+            "Creating checkpoint bundle (translation: {:?})",
+            translation
+        );
+
+        CheckpointBundle {
+            collider: Collider::cuboid(SIZE.x / 2.0, SIZE.y / 2.0),
+            sensor: Sensor,
+            active_collision_types: ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_STATIC,
+            sprite_bundle: SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(SIZE),
+                    color: COLOR,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: translation.extend(z_index::LEVEL_BASE),
+                    ..default()
+                },
+                ..default()
+            },
+            checkpoint: Checkpoint,
+        }
+    }
+}
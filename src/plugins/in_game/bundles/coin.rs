@@ -2,8 +2,9 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    plugins::in_game::components::animated_sprite::{
-        AnimatedSprite, AnimationIndices, AnimationTimer, CurrentlyAnimating,
+    plugins::in_game::{
+        assets::atlas::AtlasDescriptor,
+        components::animated_sprite::{AnimatedSprite, CurrentlyAnimating, SpriteAnimation},
     },
     z_index,
 };
@@ -14,6 +15,7 @@ const SIZE: f32 = 60.0;
 
 const TEXTURE_SIZE: Vec2 = Vec2::new(79.0, 79.0);
 pub const TEXTURE_PATH: &str = "coin.atlas.png";
+pub const ATLAS_PATH: &str = "coin.atlas.json";
 
 #[derive(Component, Default)]
 pub struct Coin;
@@ -34,15 +36,10 @@ pub struct CoinBundle {
 impl CoinBundle {
     pub fn new(
         translation: Vec2,
-        asset_server: &Res<AssetServer>,
-        texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+        texture: Handle<Image>,
+        layout: Handle<TextureAtlasLayout>,
+        descriptor: Handle<AtlasDescriptor>,
     ) -> Self {
-        let layout = TextureAtlasLayout::from_grid(TEXTURE_SIZE, 7, 2, None, None);
-        let layout = texture_atlas_layouts.add(layout);
-
-        let texture = asset_server.load(TEXTURE_PATH);
-        let animation_indices = AnimationIndices { first: 0, last: 12 };
-
         CoinBundle {
             collider: Collider::ball(SIZE / 2.0),
             sensor: Sensor,
@@ -50,12 +47,8 @@ impl CoinBundle {
                 | ActiveCollisionTypes::KINEMATIC_STATIC,
 
             animation: AnimatedSprite {
-                texture_atlas: TextureAtlas {
-                    layout,
-                    index: animation_indices.first,
-                },
-                animation_indices,
-                animation_timer: AnimationTimer(Timer::from_seconds(0.05, TimerMode::Repeating)),
+                texture_atlas: TextureAtlas { layout, index: 0 },
+                animation: SpriteAnimation::new(descriptor),
             },
             currently_animating: CurrentlyAnimating,
             sprite_bundle: SpriteBundle {
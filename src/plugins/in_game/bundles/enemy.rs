@@ -1,25 +1,44 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    plugins::in_game::components::{damage::Damage, health::Health},
-    plugins::in_game::level::ImageHandles,
+    plugins::in_game::components::{
+        character::grapple::{
+            pull::{Pulled, Weight},
+            Grappleable,
+        },
+        damage::{Damage, DamageKind, Resistances},
+        health::Health,
+        interpolated::Interpolated,
+    },
     z_index, GRAVITY,
 };
 
-use crate::plugins::in_game::player;
-
 const WIDTH: f32 = 128.0 / 2.0;
 const HEIGHT: f32 = WIDTH / TEXTURE_ASPECT_RATIO;
 const SIZE_VEC2: Vec2 = Vec2::new(WIDTH, HEIGHT);
 
-const SPEED: Vec2 = Vec2::new(2.0, 0.0);
+/// In pixels per second
+const SPEED: Vec2 = Vec2::new(120.0, 0.0);
+/// In pixels per second
+const MIN_SPEED: Vec2 = Vec2::new(12.0, 0.0);
 
 pub const TEXTURE_PATH: &str = "swamp_chomp.png";
 const TEXTURE_SIZE: Vec2 = Vec2::new(476.0, 416.0);
 const TEXTURE_ASPECT_RATIO: f32 = TEXTURE_SIZE.x / TEXTURE_SIZE.y;
 
 const INITIAL_HEALTH: f32 = 100.0;
+/// How much health the player loses when they touch an enemy.
+const DAMAGE: f32 = 25.0;
+/// Light enough for the player's grapple to pull.
+const WEIGHT: f32 = 1.0;
+/// Too heavy for the player's grapple to pull, so grappling one pulls the
+/// player to it instead.
+const HEAVY_WEIGHT: f32 = 4.0;
+/// Heavy enemies are darker, so they can be told apart.
+const HEAVY_COLOR: Color = Color::rgb(0.6, 0.6, 0.7);
 
 #[derive(Bundle)]
 pub struct EnemyBundle {
@@ -29,35 +48,42 @@ pub struct EnemyBundle {
     health: Health,
     active_events: ActiveEvents,
     damage: Damage,
+    resistances: Resistances,
     rigid_body: RigidBody,
     char_controller: KinematicCharacterController,
+    interpolated: Interpolated,
+    grappleable: Grappleable,
+    weight: Weight,
 }
 
 impl EnemyBundle {
-    pub fn new(translation: Vec2, handles: &ImageHandles) -> Self {
+    pub fn new(translation: Vec2, texture: Handle<Image>, heavy: bool) -> Self {
         EnemyBundle {
             sprite_bundle: SpriteBundle {
                 sprite: Sprite {
                     custom_size: Some(SIZE_VEC2),
+                    color: if heavy { HEAVY_COLOR } else { Color::WHITE },
                     ..default()
                 },
                 transform: Transform {
                     translation: translation.extend(z_index::LEVEL_BASE),
                     ..default()
                 },
-                texture: handles.texture.clone(),
+                texture,
                 ..default()
             },
             collider: Collider::cuboid(WIDTH / 2.0, HEIGHT / 2.0),
             enemy: Enemy::new(SPEED, 0.0, 12.0),
             health: Health::full(INITIAL_HEALTH),
             active_events: ActiveEvents::COLLISION_EVENTS,
-            damage: Damage(player::INITIAL_HEALTH),
+            damage: Damage(DAMAGE),
+            // Enemies bumping into each other doesn't hurt them
+            resistances: Resistances(HashMap::from([(DamageKind::Contact, 1.0)])),
             rigid_body: RigidBody::KinematicPositionBased,
-            char_controller: KinematicCharacterController {
-                translation: Some(SPEED),
-                ..default()
-            },
+            char_controller: KinematicCharacterController::default(),
+            interpolated: Interpolated::new(translation),
+            grappleable: Grappleable,
+            weight: Weight(if heavy { HEAVY_WEIGHT } else { WEIGHT }),
         }
     }
 }
@@ -76,7 +102,7 @@ impl Enemy {
         Enemy {
             direction: Direction::Right,
             speed,
-            min_speed: Vec2::new(0.1, 0.0),
+            min_speed: MIN_SPEED,
             left_boundary,
             right_boundary,
         }
@@ -98,9 +124,13 @@ impl Direction {
     }
 }
 
-pub fn move_enemy(mut enemies: Query<(&mut KinematicCharacterController, &Enemy)>) {
+pub fn move_enemy(
+    time: Res<Time>,
+    // Enemies being pulled by a grapple are moved by it instead
+    mut enemies: Query<(&mut KinematicCharacterController, &Enemy), Without<Pulled>>,
+) {
     for (mut char, enemy) in &mut enemies {
-        char.translation = Some(enemy.speed + GRAVITY);
+        char.translation = Some((enemy.speed + GRAVITY) * time.delta_seconds());
     }
 }
 
@@ -149,8 +179,7 @@ pub fn swap_direction(mut enemies: Query<(&mut Enemy, &Transform)>) {
             0.5 - (rel_percent - 0.5)
         };
 
-        let scale_factor = 2.0 * enemy.direction.signum();
-        let x = x.max(0.0).sqrt() + enemy.min_speed.x;
-        enemy.speed.x = x * scale_factor;
+        let x = x.max(0.0).sqrt() * SPEED.x + enemy.min_speed.x;
+        enemy.speed.x = x * enemy.direction.signum();
     }
 }
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{plugins::in_game::components::power_up::PowerUpKind, z_index};
+
+/// In pixels
+const SIZE: f32 = 32.0;
+
+/// Gives the player a power-up when they touch it.
+#[derive(Component, Debug)]
+pub struct PowerUp(pub PowerUpKind);
+
+#[derive(Bundle)]
+pub struct PowerUpBundle {
+    collider: Collider,
+    sensor: Sensor,
+    active_collision_types: ActiveCollisionTypes,
+    sprite_bundle: SpriteBundle,
+    power_up: PowerUp,
+}
+
+impl PowerUpBundle {
+    pub fn new(translation: Vec2, kind: PowerUpKind) -> Self {
+        trace!(
+            "Creating {:?} power-up bundle (translation: {:?})",
+            kind,
+            translation
+        );
+
+        PowerUpBundle {
+            collider: Collider::ball(SIZE / 2.0),
+            sensor: Sensor,
+            active_collision_types: ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_STATIC,
+            sprite_bundle: SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(SIZE)),
+                    color: kind.color(),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: translation.extend(z_index::LEVEL_BASE),
+                    ..default()
+                },
+                ..default()
+            },
+            power_up: PowerUp(kind),
+        }
+    }
+}
@@ -1,12 +1,17 @@
 pub mod animated_sprite;
 pub mod background;
 pub mod character;
+pub mod checkpoint;
 pub mod collect_coin;
 pub mod damage;
+pub mod enemy_death;
 pub mod health;
-pub mod kills_player;
+pub mod interpolated;
+pub mod invulnerable;
+pub mod lives;
 pub mod npc_movement;
 pub mod player_win;
+pub mod power_up;
 
 pub struct ComponentsPlugin;
 impl bevy::prelude::Plugin for ComponentsPlugin {
@@ -15,19 +20,17 @@ impl bevy::prelude::Plugin for ComponentsPlugin {
             animated_sprite::animated_sprite_plugin,
             background::background_plugin,
             character::character_plugin,
+            checkpoint::checkpoint_plugin,
             collect_coin::coin_plugin,
             damage::damage_plugin,
-            kills_player::kills_player_plugin,
+            enemy_death::enemy_death_plugin,
+            health::health_plugin,
+            interpolated::interpolated_plugin,
+            invulnerable::invulnerable_plugin,
+            lives::lives_plugin,
             npc_movement::npc_movement_plugin,
             player_win::player_win_plugin,
+            power_up::power_up_plugin,
         ));
     }
 }
-
-pub fn cleanup(
-    next_grapple_state: bevy::prelude::ResMut<
-        bevy::prelude::NextState<character::grapple::GrappleState>,
-    >,
-) {
-    character::cleanup(next_grapple_state);
-}
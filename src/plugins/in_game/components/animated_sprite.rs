@@ -1,37 +1,67 @@
 use bevy::prelude::*;
 
+use crate::plugins::in_game::assets::atlas::{AtlasDescriptor, LoopMode};
+
 pub fn animated_sprite_plugin(app: &mut App) {
-    app.add_systems(Update, animate_sprite);
+    app.add_event::<AnimationEvent>()
+        .add_systems(Update, (animate_sprite, log_animation_events).chain());
 }
 
 // Derived from https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_sheet.rs
 
-#[derive(Bundle, Default)]
+#[derive(Bundle)]
 pub struct AnimatedSprite {
     pub texture_atlas: TextureAtlas,
-    pub animation_indices: AnimationIndices,
-    pub animation_timer: AnimationTimer,
+    pub animation: SpriteAnimation,
 }
 
-#[derive(Component, Default)]
-pub struct AnimationIndices {
-    pub first: usize,
-    pub last: usize,
+/// Plays the clips from an atlas descriptor by name.
+#[derive(Component)]
+pub struct SpriteAnimation {
+    descriptor: Handle<AtlasDescriptor>,
+    /// The clip to play, or the descriptor's default clip if `None`.
+    clip: Option<String>,
+    /// Seconds that the current frame has been shown for.
+    elapsed: f32,
+    /// Whether a ping-pong clip is playing backwards.
+    reversed: bool,
+    /// Whether the clip's first frame has been shown yet.
+    started: bool,
 }
 
-impl AnimationIndices {
-    pub fn next(&self, index: usize) -> usize {
-        let index = index + 1;
-        if index >= self.last {
-            self.first
-        } else {
-            index
+impl SpriteAnimation {
+    pub fn new(descriptor: Handle<AtlasDescriptor>) -> Self {
+        SpriteAnimation {
+            descriptor,
+            clip: None,
+            elapsed: 0.0,
+            reversed: false,
+            started: false,
+        }
+    }
+
+    /// Switches to the clip called `name`, starting it from the beginning.
+    ///
+    /// Does nothing if the clip is already playing.
+    pub fn play(&mut self, name: &str) {
+        if self.clip.as_deref() == Some(name) {
+            return;
         }
+
+        trace!("Playing clip {:?} -> {:?}", self.clip, name);
+        self.clip = Some(name.to_string());
+        self.elapsed = 0.0;
+        self.reversed = false;
+        self.started = false;
     }
 }
 
-#[derive(Component, Default, Deref, DerefMut)]
-pub struct AnimationTimer(pub Timer);
+/// Sent when a frame with an event is shown.
+#[derive(Event, Debug)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub name: String,
+}
 
 /// Make the sprite animate
 #[derive(Component)]
@@ -39,15 +69,84 @@ pub struct CurrentlyAnimating;
 
 fn animate_sprite(
     time: Res<Time>,
-    mut query: Query<
-        (&AnimationIndices, &mut AnimationTimer, &mut TextureAtlas),
-        With<CurrentlyAnimating>,
-    >,
+    descriptors: Res<Assets<AtlasDescriptor>>,
+    mut query: Query<(Entity, &mut SpriteAnimation, &mut TextureAtlas), With<CurrentlyAnimating>>,
+    mut events: EventWriter<AnimationEvent>,
 ) {
-    for (indices, mut timer, mut atlas) in &mut query {
-        timer.tick(time.delta());
-        if timer.just_finished() {
-            atlas.index = indices.next(atlas.index);
+    for (entity, mut animation, mut atlas) in &mut query {
+        let Some(descriptor) = descriptors.get(&animation.descriptor) else {
+            continue;
+        };
+        let clip_name = animation
+            .clip
+            .as_deref()
+            .unwrap_or(&descriptor.default_clip);
+        let Some(clip) = descriptor.clips.get(clip_name) else {
+            warn!("Atlas has no clip named `{}`", clip_name);
+            continue;
+        };
+
+        if !animation.started {
+            animation.started = true;
+            atlas.index = clip.first;
+        } else {
+            animation.elapsed += time.delta_seconds();
+            if animation.elapsed < clip.duration(atlas.index) {
+                continue;
+            }
+            animation.elapsed -= clip.duration(atlas.index);
+
+            let Some(next) = next_frame(
+                clip.mode,
+                clip.first,
+                clip.last,
+                atlas.index,
+                &mut animation.reversed,
+            ) else {
+                // Finished a clip that doesn't loop
+                animation.elapsed = 0.0;
+                continue;
+            };
+            atlas.index = next;
+        }
+
+        if let Some(name) = clip.events.get(&atlas.index) {
+            events.send(AnimationEvent {
+                entity,
+                name: name.clone(),
+            });
+        }
+    }
+}
+
+/// The frame after `index`, or `None` if the clip has finished.
+fn next_frame(
+    mode: LoopMode,
+    first: usize,
+    last: usize,
+    index: usize,
+    reversed: &mut bool,
+) -> Option<usize> {
+    match mode {
+        LoopMode::Loop if index >= last => Some(first),
+        LoopMode::Once if index >= last => None,
+        LoopMode::Loop | LoopMode::Once => Some(index + 1),
+        LoopMode::PingPong => {
+            if first == last {
+                return Some(first);
+            }
+            if *reversed && index <= first {
+                *reversed = false;
+            } else if !*reversed && index >= last {
+                *reversed = true;
+            }
+            Some(if *reversed { index - 1 } else { index + 1 })
         }
     }
 }
+
+fn log_animation_events(mut events: EventReader<AnimationEvent>) {
+    for event in events.read() {
+        trace!("Animation event `{}` on {:?}", event.name, event.entity);
+    }
+}
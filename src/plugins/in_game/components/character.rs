@@ -2,22 +2,41 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use leafwing_input_manager::prelude::*;
 
-use crate::{
-    plugins::in_game::{player, InGameSet},
-    GRAVITY,
-};
+use crate::plugins::in_game::{components::damage::DamageSet, InGameSet};
 
-use super::animated_sprite::CurrentlyAnimating;
+use self::velocity::KinematicVelocity;
 
+/// How fast the grapple pulls the character, in pixels per second.
+const GRAPPLE_SPEED: f32 = 600.0;
+
+pub mod attack;
+pub mod dash;
 pub mod grapple;
+pub mod input;
 pub mod jump;
+pub mod velocity;
+pub mod wall;
 
 pub fn character_plugin(app: &mut App) {
     app.add_plugins(crate::plugins::in_game::components::character::grapple::grapple_plugin)
         // FIXME: maybe move the jump system somewhere else
         .add_systems(
             FixedUpdate,
-            (jump::stop_jump, r#move, jump::jump)
+            (
+                jump::stop_jump,
+                velocity::apply_gravity,
+                r#move,
+                wall::detect_walls,
+                jump::update_jump_assist,
+                jump::start_jump,
+                jump::jump,
+                wall::wall_jump_kick,
+                wall::wall_slide,
+                dash::update_dash_state,
+                dash::start_dash,
+                dash::dash,
+                velocity::apply_velocity,
+            )
                 .in_set(InGameSet)
                 // Must run in order because otherwise:
                 // a) `stop_jump` could run immediately after `jump`, ending it
@@ -26,21 +45,66 @@ pub fn character_plugin(app: &mut App) {
                 //    pov the jump ends 1 frame late.
                 // c) `jump` could run before r#move, meaning that the jump
                 //    starts 1 frame late.
+                // d) `start_jump` could run before the coyote time, jump
+                //    buffer and walls are updated, so a jump press would be
+                //    missed.
+                // e) the wall systems could run before r#move or `jump`, and
+                //    have their velocity overwritten.
+                // f) the same for `dash`, which replaces all other movement.
+                // g) `apply_velocity` could run before the velocity is
+                //    finished being changed.
                 .chain()
                 // Must run after physics or its possible that the jump will be
                 // stopped before the character has a chance to actually move.
                 // FIXME: double check that this is the right `SystemSet`
                 .after(PhysicsSet::Writeback),
-        );
-}
-
-pub fn cleanup(next_grapple_state: ResMut<NextState<grapple::GrappleState>>) {
-    grapple::cleanup(next_grapple_state);
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                attack::start_attack,
+                attack::move_projectiles,
+                attack::projectile_hits,
+            )
+                .chain()
+                .in_set(InGameSet)
+                // Hits are checked against the latest physics step, and their
+                // damage is dealt in the same tick
+                .after(PhysicsSet::Writeback)
+                .before(DamageSet),
+        )
+        .add_systems(Update, input::latch_presses.in_set(InGameSet))
+        .add_systems(FixedLast, input::clear_presses.in_set(InGameSet));
 }
 
 #[derive(Component, Default)]
 pub struct Character {
+    /// The fastest the character can walk.
     pub movement_speed: f32,
+    pub movement: velocity::MovementSettings,
+    pub jump: jump::JumpSettings,
+    pub grapple: grapple::GrappleSettings,
+    pub abilities: Abilities,
+}
+
+/// Movement abilities that a character may or may not have.
+///
+/// These can be granted by levels or power-ups.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub struct Abilities {
+    /// Fall slower while pressing into a wall.
+    #[serde(default)]
+    pub wall_slide: bool,
+    /// Jump off of walls.
+    #[serde(default)]
+    pub wall_jump: bool,
+    /// How many extra jumps the character can do before landing.
+    #[serde(default)]
+    pub air_jumps: u32,
+    /// How the grapple moves the character. Levels get `pull` unless they ask
+    /// for `swing`.
+    #[serde(default)]
+    pub grapple_mode: grapple::GrappleMode,
 }
 
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
@@ -49,77 +113,82 @@ pub enum Action {
     Right,
     Jump,
     Grapple,
+    Dash,
+    /// Shorten the grapple's rope.
+    ReelIn,
+    /// Lengthen the grapple's rope.
+    ReelOut,
+    /// Which way to aim the grapple, when not using the mouse.
+    Aim,
+    /// Spit a bubble the way the character is facing.
+    Attack,
 }
 
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
 pub fn r#move(
-    action_state_query: Query<&ActionState<Action>, With<Character>>,
-    mut player_query: Query<(
-        Entity,
-        &mut KinematicCharacterController,
-        &mut Sprite,
-        &Character,
-    )>,
-    char_controller_output: Query<Option<&KinematicCharacterControllerOutput>, With<Character>>,
-    mut commands: Commands,
+    time: Res<Time>,
+    mut char_query: Query<
+        (
+            &ActionState<Action>,
+            &mut KinematicVelocity,
+            &mut Sprite,
+            &Character,
+            Option<&KinematicCharacterControllerOutput>,
+        ),
+        // Swinging characters are moved by the rope instead
+        Without<grapple::swing::Swinging>,
+    >,
 ) {
-    let action_state = action_state_query.single();
-    let Ok((entity, mut char_controller, mut sprite, char)) = player_query.get_single_mut() else {
-        return;
-    };
-
-    let actions = action_state.get_pressed();
+    for (action_state, mut velocity, mut sprite, char, output) in &mut char_query {
+        let actions = action_state.get_pressed();
 
-    if actions.is_empty() {
-        trace!("No actions pressed.");
-        commands.entity(entity).remove::<CurrentlyAnimating>();
-    } else {
-        trace!("Moving character.");
-    }
-
-    let translation = &mut char_controller.translation.unwrap_or(GRAVITY);
+        if actions.is_empty() {
+            trace!("No actions pressed.");
+        } else {
+            trace!("Moving character.");
+        }
 
-    for action in actions {
-        trace!("Action: {:#?}", action);
-        match action {
-            Action::Left => {
-                translation.x = -char.movement_speed;
-                sprite.flip_x = true;
-                commands.entity(entity).insert(CurrentlyAnimating);
-            }
-            Action::Right => {
-                translation.x = char.movement_speed;
-                sprite.flip_x = false;
-                commands.entity(entity).insert(CurrentlyAnimating);
-            }
-            Action::Jump => {
-                if let Some(output) = char_controller_output.single() {
-                    if output.grounded {
-                        info!("Character is grounded, starting jump.");
-                        commands.entity(entity).remove::<CurrentlyAnimating>();
-                        commands.entity(entity).insert(player::jump_component());
-                    } else {
-                        debug!("Character is not grounded, can't jump.");
-                    }
-                } else {
-                    warn!("No character controller output found, can't jump.");
+        let mut direction = 0.0;
+        for action in actions {
+            trace!("Action: {:#?}", action);
+            match action {
+                Action::Left => {
+                    direction -= 1.0;
+                    sprite.flip_x = true;
+                }
+                Action::Right => {
+                    direction += 1.0;
+                    sprite.flip_x = false;
                 }
+                // Do nothing, these are handled elsewhere.
+                Action::Jump
+                | Action::Grapple
+                | Action::Dash
+                | Action::ReelIn
+                | Action::ReelOut
+                | Action::Aim
+                | Action::Attack => {}
             }
-            Action::Grapple => { /* Do nothing, this is handled elsewhere. */ }
         }
-    }
 
-    char_controller.translation = Some(*translation);
+        // Speed up towards the movement speed, or slow down if there's no
+        // input. This keeps any extra momentum (eg from grappling) instead of
+        // stopping instantly.
+        let grounded = output.is_some_and(|output| output.grounded);
+        let rate = char.movement.rate(grounded, direction != 0.0);
+        velocity.x = velocity::move_towards(
+            velocity.x,
+            direction * char.movement_speed,
+            rate * time.delta_seconds(),
+        );
+    }
 }
 
-/// Add a force to the player in the given direction (to be used for grappling).
-pub fn add_grapple_force(
-    mut player_query: Query<&mut KinematicCharacterController, With<Character>>,
-    direction: Vec2,
-) {
-    let char_controller = &mut player_query.single_mut();
-
-    // Completely replace player translation with grapple force
-    let force = direction * 10.0;
-    char_controller.translation = Some(force);
+/// Add a force to a character in the given direction (to be used for grappling).
+pub fn add_grapple_force(velocity: &mut KinematicVelocity, direction: Vec2) {
+    // Completely replace player velocity with grapple force
+    let force = direction * GRAPPLE_SPEED;
+    velocity.0 = force;
     trace!("Setting grapple force on player to: {:?}", force);
 }
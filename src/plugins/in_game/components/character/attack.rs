@@ -0,0 +1,169 @@
+//! Characters can spit bubbles, which hurt whatever they hit.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    plugins::in_game::{
+        components::{
+            damage::{DamageEvent, DamageKind},
+            health::Health,
+            interpolated::Interpolated,
+        },
+        level,
+        player::Player,
+    },
+    z_index,
+};
+
+use super::{input::LatchedPresses, Action, Character};
+
+/// In pixels per second
+const SPIT_SPEED: f32 = 540.0;
+/// How long a bubble lasts if it doesn't hit anything, in seconds.
+const SPIT_LIFETIME: f32 = 0.8;
+/// How long after spitting before the character can spit again, in seconds.
+const SPIT_COOLDOWN: f32 = 0.35;
+const SPIT_DAMAGE: f32 = 50.0;
+/// In pixels
+const SPIT_SIZE: f32 = 16.0;
+const SPIT_COLOR: Color = Color::rgba(0.7, 0.9, 1.0, 0.7);
+/// Where bubbles start, relative to the character (facing right), in pixels.
+const SPIT_OFFSET: Vec2 = Vec2::new(32.0, 8.0);
+
+/// Keeps track of whether a character is allowed to spit.
+#[derive(Component, Debug, Clone, Default)]
+pub struct AttackState {
+    cooldown_remaining: f32,
+}
+
+/// A spit bubble.
+#[derive(Component, Debug, Clone)]
+pub struct Projectile {
+    /// The character that spat it, which it can't hit.
+    owner: Entity,
+    /// In pixels per second
+    velocity: Vec2,
+    /// In seconds
+    remaining: f32,
+}
+
+pub fn start_attack(
+    time: Res<Time>,
+    mut char_query: Query<
+        (
+            Entity,
+            &LatchedPresses,
+            &mut AttackState,
+            &Transform,
+            &Sprite,
+        ),
+        With<Character>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, latched, mut attack_state, transform, sprite) in &mut char_query {
+        attack_state.cooldown_remaining =
+            (attack_state.cooldown_remaining - time.delta_seconds()).max(0.0);
+
+        if !latched.just_pressed(Action::Attack) {
+            continue;
+        }
+        if attack_state.cooldown_remaining > 0.0 {
+            debug!("Attack is on cooldown, can't spit.");
+            continue;
+        }
+        attack_state.cooldown_remaining = SPIT_COOLDOWN;
+
+        // Spit the way the character is facing
+        let direction = if sprite.flip_x { -1.0 } else { 1.0 };
+        let translation =
+            transform.translation.truncate() + SPIT_OFFSET * Vec2::new(direction, 1.0);
+        debug!("Spitting bubble from {:?}", translation);
+
+        level::spawn_entity(
+            &mut commands,
+            (
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: SPIT_COLOR,
+                        custom_size: Some(Vec2::splat(SPIT_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(translation.extend(z_index::LEVEL_BASE)),
+                    ..default()
+                },
+                Projectile {
+                    owner: entity,
+                    velocity: Vec2::new(direction * SPIT_SPEED, 0.0),
+                    remaining: SPIT_LIFETIME,
+                },
+                Interpolated::new(translation),
+                RigidBody::KinematicPositionBased,
+                Collider::ball(SPIT_SIZE / 2.0),
+                Sensor,
+                ActiveCollisionTypes::default()
+                    | ActiveCollisionTypes::KINEMATIC_STATIC
+                    | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+            ),
+        );
+    }
+}
+
+pub fn move_projectiles(
+    time: Res<Time>,
+    mut projectile_query: Query<(Entity, &mut Projectile, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, mut projectile, mut transform) in &mut projectile_query {
+        projectile.remaining -= time.delta_seconds();
+        if projectile.remaining <= 0.0 {
+            trace!("Bubble {:?} popped on its own", entity);
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation += (projectile.velocity * time.delta_seconds()).extend(0.0);
+    }
+}
+
+/// Pops bubbles when they touch terrain or something that can be hurt.
+pub fn projectile_hits(
+    rapier_context: Res<RapierContext>,
+    projectile_query: Query<(Entity, &Projectile)>,
+    // Other sensors (eg coins) and bubbles don't pop bubbles
+    solid_query: Query<(), (Without<Sensor>, Without<Projectile>)>,
+    health_query: Query<(), With<Health>>,
+    player_query: Query<(), With<Player>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut commands: Commands,
+) {
+    for (entity, projectile) in &projectile_query {
+        // Already popped by `move_projectiles`
+        if projectile.remaining <= 0.0 {
+            continue;
+        }
+
+        let hit = rapier_context
+            .intersection_pairs_with(entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == entity { b } else { a })
+            .find(|other| *other != projectile.owner && solid_query.contains(*other));
+        let Some(hit) = hit else {
+            continue;
+        };
+
+        debug!("Bubble {:?} hit {:?}", entity, hit);
+        // Players' bubbles still pop on each other, but don't hurt
+        let friendly_fire = player_query.contains(projectile.owner) && player_query.contains(hit);
+        if health_query.contains(hit) && !friendly_fire {
+            damage_events.send(DamageEvent {
+                source: projectile.owner,
+                target: hit,
+                amount: SPIT_DAMAGE,
+                kind: DamageKind::Projectile,
+            });
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::KinematicCharacterControllerOutput;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::plugins::in_game::components::invulnerable::{self, Invulnerable};
+
+use super::{
+    grapple::{GrappleState, SetGrappleState},
+    input::LatchedPresses,
+    jump::JumpComponent,
+    velocity::KinematicVelocity,
+    wall::WallJumpKick,
+    Action, Character,
+};
+
+/// How far a dash goes, in pixels.
+const DASH_DISTANCE: f32 = 160.0;
+/// How long a dash takes, in seconds.
+const DASH_TIME: f32 = 0.15;
+/// How long after a dash ends before the character can dash again, in seconds.
+const DASH_COOLDOWN: f32 = 0.5;
+/// How long the character can't be hurt for after starting a dash, in seconds.
+const DASH_INVULNERABILITY_TIME: f32 = 0.25;
+
+/// Keeps track of whether a character is allowed to dash.
+#[derive(Component, Debug, Clone, Default)]
+pub struct DashState {
+    cooldown_remaining: f32,
+    /// Whether the character has dashed since they were last on the ground.
+    air_dash_used: bool,
+}
+
+/// The character is in the middle of a dash.
+#[derive(Component, Debug, Clone)]
+pub struct Dashing {
+    /// `-1.0` for left, `1.0` for right.
+    direction: f32,
+    remaining: f32,
+}
+
+pub fn update_dash_state(
+    time: Res<Time>,
+    mut char_query: Query<(
+        &mut DashState,
+        Option<&KinematicCharacterControllerOutput>,
+        Has<Dashing>,
+    )>,
+) {
+    for (mut dash_state, output, dashing) in &mut char_query {
+        if output.is_some_and(|output| output.grounded) && !dashing {
+            dash_state.air_dash_used = false;
+        }
+        dash_state.cooldown_remaining =
+            (dash_state.cooldown_remaining - time.delta_seconds()).max(0.0);
+    }
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+pub fn start_dash(
+    mut char_query: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &LatchedPresses,
+            &mut DashState,
+            &Sprite,
+            Option<&KinematicCharacterControllerOutput>,
+            Option<&GrappleState>,
+        ),
+        (With<Character>, Without<Dashing>),
+    >,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
+    mut commands: Commands,
+) {
+    for (entity, action_state, latched, mut dash_state, sprite, output, grapple_state) in
+        &mut char_query
+    {
+        if !latched.just_pressed(Action::Dash) {
+            continue;
+        }
+        if dash_state.cooldown_remaining > 0.0 {
+            debug!("Dash is on cooldown, can't dash.");
+            continue;
+        }
+
+        let grounded = output.is_some_and(|output| output.grounded);
+        if !grounded {
+            if dash_state.air_dash_used {
+                debug!("Already dashed in the air, can't dash.");
+                continue;
+            }
+            dash_state.air_dash_used = true;
+        }
+
+        // Dash in the direction being pressed, or the way the character is
+        // facing if nothing is pressed
+        let direction = if action_state.pressed(&Action::Left) {
+            -1.0
+        } else if action_state.pressed(&Action::Right) {
+            1.0
+        } else if sprite.flip_x {
+            -1.0
+        } else {
+            1.0
+        };
+
+        info!("Starting dash (direction: {})", direction);
+
+        // Dashing replaces any other movement
+        if grapple_state.is_some_and(|state| *state != GrappleState::Idle) {
+            info!("Cancelling grapple because of dash (-> idle)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Idle,
+            });
+        }
+        commands
+            .entity(entity)
+            .remove::<(JumpComponent, WallJumpKick)>()
+            .insert(Dashing {
+                direction,
+                remaining: DASH_TIME,
+            });
+        // Doesn't cut short the invulnerability from being hit
+        invulnerable::make_invulnerable(
+            &mut commands,
+            entity,
+            Invulnerable::for_seconds(DASH_INVULNERABILITY_TIME),
+        );
+    }
+}
+
+pub fn dash(
+    time: Res<Time>,
+    mut char_query: Query<(
+        Entity,
+        &Character,
+        &mut Dashing,
+        &mut DashState,
+        &mut KinematicVelocity,
+        &mut Sprite,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, char, mut dashing, mut dash_state, mut velocity, mut sprite) in &mut char_query {
+        // Move at a constant speed (and ignore gravity) so the dash always
+        // covers the same distance
+        let speed = DASH_DISTANCE / DASH_TIME;
+        velocity.0 = Vec2::new(dashing.direction * speed, 0.0);
+        sprite.flip_x = dashing.direction < 0.0;
+
+        dashing.remaining -= time.delta_seconds();
+        if dashing.remaining <= 0.0 {
+            debug!("Dash finished");
+            dash_state.cooldown_remaining = DASH_COOLDOWN;
+            // Don't keep all of the dash's speed, or it would carry on for a
+            // long time afterwards
+            velocity.x = dashing.direction * char.movement_speed;
+            commands.entity(entity).remove::<Dashing>();
+        }
+    }
+}
@@ -1,44 +1,137 @@
 use bevy::{prelude::*, window::PrimaryWindow};
-use bevy_rapier2d::prelude::{
-    KinematicCharacterController, KinematicCharacterControllerOutput, QueryFilter, RapierContext,
-};
+use bevy_rapier2d::prelude::{KinematicCharacterControllerOutput, QueryFilter, RapierContext};
 use leafwing_input_manager::prelude::ActionState;
 
-use crate::plugins::in_game::{
-    components::character::{add_grapple_force, Action, Character},
-    player::Player,
-    InGameSet,
+use crate::{
+    plugins::in_game::{
+        components::character::{
+            add_grapple_force, dash, input::LatchedPresses, jump, velocity,
+            velocity::KinematicVelocity, wall, Action, Character,
+        },
+        components::interpolated::{Interpolated, InterpolationSet},
+        level,
+        player::Player,
+        InGameSet,
+    },
+    z_index,
 };
 
-const GUIDELINE_DISTANCE: f32 = 50.0;
-const GUIDELINE_SIZE: f32 = 10.0;
+use self::{pull::PullingTarget, swing::Swinging};
+
+pub mod pull;
+pub mod swing;
+pub mod visuals;
+
+/// How far off (in radians) a grappleable surface can be from where a stick
+/// or keys are aiming and still be snapped to.
+const AIM_ASSIST_ANGLE: f32 = 0.35;
+/// How far a stick has to be pushed before it counts as aiming.
+const AIM_DEADZONE: f32 = 0.3;
 
 pub fn grapple_plugin(app: &mut App) {
     debug!("Building GrapplePlugin");
 
-    app.init_state::<GrappleState>()
-        .init_resource::<Guideline>()
-        .add_systems(OnExit(GrappleState::Grappling), end_grapple)
-        .add_systems(OnExit(GrappleState::Aiming), remove_guideline_system)
+    app.add_event::<SetGrappleState>()
+        .add_event::<GrappleStateChanged>()
         .add_systems(
             // FIXME: should this *all* be in FixedUpdate?
             FixedUpdate,
             (
-                idle.run_if(in_state(GrappleState::Idle)),
-                (aim, aim_marker, aim_guideline).run_if(in_state(GrappleState::Aiming)),
+                tick_cooldown,
+                idle,
+                (aim, aim_marker),
                 (
                     grapple,
+                    track_target
+                        .before(manage_grapple)
+                        .before(swing::swing)
+                        .before(pull::pull_target),
                     manage_grapple,
                     should_grapple_end,
                     end_grapple_on_other_input,
-                )
-                    .run_if(in_state(GrappleState::Grappling)),
+                    swing::swing,
+                    swing::let_go_on_jump,
+                    pull::pull_target,
+                ),
             )
-                .in_set(InGameSet),
+                .in_set(InGameSet)
+                // The grapple replaces walking/jumping/falling, but walls and
+                // dashing take priority over it.
+                .after(jump::jump)
+                .before(wall::wall_jump_kick),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                apply_grapple_state,
+                // What `OnEnter`/`OnExit` would do if every character didn't
+                // have its own state
+                (
+                    (cancel_jump, swing::attach_rope, pull::start_pulling),
+                    (
+                        end_grapple,
+                        swing::detach_rope,
+                        pull::stop_pulling,
+                        start_cooldown,
+                        remove_aim_preview,
+                    ),
+                ),
+            )
+                .chain()
+                .in_set(InGameSet)
+                // Dashing cancels the grapple, and the changes should be in
+                // place before the character moves
+                .after(dash::start_dash)
+                .before(velocity::apply_velocity),
+        )
+        // Drawn every frame (instead of every tick) from the interpolated
+        // position, so it doesn't lag behind the character
+        .add_systems(
+            Update,
+            (visuals::aim_guideline, visuals::draw_rope)
+                .in_set(InGameSet)
+                .after(InterpolationSet),
         );
 }
 
-#[derive(States, Debug, Default, Clone, Eq, PartialEq, Hash)]
+/// How the grapple moves the character once it hits something.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrappleMode {
+    /// Pull the character straight towards the target.
+    #[default]
+    Pull,
+    /// Swing from the target on a rope.
+    Swing,
+}
+
+/// How far a character's grapple reaches and how often it can be used.
+#[derive(Clone, Debug, Default)]
+pub struct GrappleSettings {
+    /// In pixels
+    pub range: f32,
+    /// How long after letting go before the grapple can be used again, in
+    /// seconds.
+    pub cooldown: f32,
+    /// The heaviest `Weight` the grapple pulls towards the character. Anything
+    /// heavier pulls the character towards it instead.
+    pub max_pull_weight: f32,
+}
+
+/// The grapple can attach to this entity.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct Grappleable;
+
+/// The character can't grapple until this runs out.
+#[derive(Component, Debug, Clone)]
+pub struct GrappleCooldown {
+    remaining: f32,
+}
+
+/// What a character's grapple is doing.
+///
+/// Only changed by `apply_grapple_state`, use `SetGrappleState` to change it.
+#[derive(Component, Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum GrappleState {
     #[default]
     Idle,
@@ -56,305 +149,588 @@ impl GrappleState {
     }
 }
 
-#[derive(Resource)]
-struct TargetPos(
-    /// The position of the target.
-    Vec2,
-    /// The entity that the target is attached to.
-    Entity,
-    /// The entity of the target.
-    Entity,
-);
+/// Changes a character's `GrappleState`, the same way `NextState` does for
+/// `States`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SetGrappleState {
+    pub entity: Entity,
+    pub state: GrappleState,
+}
 
-#[derive(Resource, Default)]
-struct Guideline(Vec<Entity>);
+/// Sent by `apply_grapple_state` whenever a character's `GrappleState`
+/// changes.
+#[derive(Event, Debug, Clone, Copy)]
+struct GrappleStateChanged {
+    entity: Entity,
+    from: GrappleState,
+    to: GrappleState,
+}
 
-pub fn cleanup(mut next_grapple_state: ResMut<NextState<GrappleState>>) {
-    info!("Cleaning up grapple.");
-    next_grapple_state.set(GrappleState::Idle);
+/// Everything a character needs to use the grapple.
+#[derive(Bundle, Default)]
+pub struct GrappleBundle {
+    state: GrappleState,
+    guideline: visuals::Guideline,
+    rope: visuals::Rope,
 }
 
-fn idle(
-    action_state_query: Query<&ActionState<Action>, With<Character>>,
-    mut next_grapple_state: ResMut<NextState<GrappleState>>,
+/// What a character's grapple is attached to.
+#[derive(Component)]
+struct TargetPos {
+    /// Where the grapple is attached, in world space. This is kept up to
+    /// date with the target by `track_target`.
+    point: Vec2,
+    /// Where the grapple is attached, relative to the target.
+    local_point: Vec3,
+    /// The entity that the grapple is attached to.
+    target: Entity,
+    /// The marker showing where the grapple is attached.
+    marker: Entity,
+}
+
+impl TargetPos {
+    fn new(point: Vec2, target: Entity, target_transform: &Transform, marker: Entity) -> Self {
+        let local_point = target_transform
+            .compute_affine()
+            .inverse()
+            .transform_point3(point.extend(0.0));
+
+        TargetPos {
+            point,
+            local_point,
+            target,
+            marker,
+        }
+    }
+}
+
+/// The marker showing where a character's grapple attaches.
+///
+/// It is kept from when the character first aims at something until the
+/// grapple ends, and is moved (or hidden) instead of being respawned.
+#[derive(Component)]
+struct Marker(Entity);
+
+/// Set on the marker entity itself, so it can be queried separately from
+/// grapple targets.
+#[derive(Component)]
+struct MarkerSprite;
+
+/// Where a character's grapple is being aimed, used to draw the guideline.
+#[derive(Component)]
+struct AimPreview {
+    /// Where the guideline ends (the target, or wherever the ray stopped).
+    end: Vec2,
+    /// Whether letting go of the grapple would attach to something.
+    valid: bool,
+}
+
+fn apply_grapple_state(
+    mut set_events: EventReader<SetGrappleState>,
+    mut state_query: Query<&mut GrappleState>,
+    mut changed_events: EventWriter<GrappleStateChanged>,
 ) {
-    let action_state = action_state_query.single();
+    for SetGrappleState { entity, state } in set_events.read() {
+        let Ok(mut current) = state_query.get_mut(*entity) else {
+            trace!("Can't set grapple state of {:?}, it has none", entity);
+            continue;
+        };
+        if *current == *state {
+            continue;
+        }
 
-    let just_pressed = action_state.get_just_pressed();
-    let just_released = action_state.get_just_released();
+        debug!(
+            "Grapple state of {:?}: {:?} -> {:?}",
+            entity, *current, state
+        );
+        changed_events.send(GrappleStateChanged {
+            entity: *entity,
+            from: *current,
+            to: *state,
+        });
+        *current = *state;
+    }
+}
 
-    // Only start aiming if grapple was just pressed and not released
-    if just_pressed.contains(&Action::Grapple) && !just_released.contains(&Action::Grapple) {
-        info!("Starting grapple aiming (idle -> aiming)");
-        next_grapple_state.set(GrappleState::Idle.next());
+fn idle(
+    char_query: Query<
+        (Entity, &GrappleState, &LatchedPresses, Has<GrappleCooldown>),
+        With<Character>,
+    >,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
+) {
+    for (entity, state, latched, on_cooldown) in &char_query {
+        if *state != GrappleState::Idle {
+            continue;
+        }
+
+        // Only start aiming if grapple was just pressed and not released
+        if latched.just_pressed(Action::Grapple) && !latched.just_released(Action::Grapple) {
+            if on_cooldown {
+                debug!("Grapple is on cooldown, can't aim.");
+                continue;
+            }
+
+            info!("Starting grapple aiming (idle -> aiming)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Idle.next(),
+            });
+        }
     }
 }
 
 fn aim(
-    action_state_query: Query<&ActionState<Action>, With<Character>>,
-    mut next_grapple_state: ResMut<NextState<GrappleState>>,
+    char_query: Query<(Entity, &GrappleState, &LatchedPresses), With<Character>>,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
 ) {
-    let action_state = action_state_query.single();
-    let just_released = action_state.get_just_released();
+    for (entity, state, latched) in &char_query {
+        if *state != GrappleState::Aiming {
+            continue;
+        }
 
-    // If the key was just released, stop aiming and start grappling
-    if just_released.contains(&Action::Grapple) {
-        info!("Starting grapple (aiming -> grappling)");
-        next_grapple_state.set(GrappleState::Aiming.next());
+        // If the key was just released, stop aiming and start grappling
+        if latched.just_released(Action::Grapple) {
+            info!("Starting grapple (aiming -> grappling)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Aiming.next(),
+            });
+        }
     }
 }
 
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex.
+// The arguments are all system params, which bevy passes in itself
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn aim_marker(
     rapier_context: Res<RapierContext>,
     window_query: Query<&Window, With<PrimaryWindow>>,
-    char_query: Query<(Entity, &Transform), With<Character>>,
+    mut char_query: Query<
+        (
+            Entity,
+            &GrappleState,
+            &Transform,
+            &Character,
+            &ActionState<Action>,
+            Option<&Player>,
+            Option<&mut TargetPos>,
+            Option<&Marker>,
+        ),
+        Without<MarkerSprite>,
+    >,
     camera_query: Query<(&Camera, &GlobalTransform)>,
-    target_pos: Option<Res<TargetPos>>,
-    mut commands: Commands,
-) {
-    // Clear old marker
-    if let Some(target_pos) = target_pos {
-        remove_target_pos(&mut commands, target_pos.2);
-    }
-
-    let Ok((point, target)) =
-        cast_grapple_ray(rapier_context, window_query, char_query, camera_query)
-    else {
-        trace!("No result for grapple raycast");
-        return;
-    };
-
-    // Add grapple marker
-    let marker = add_grapple_marker(&mut commands, &point);
-
-    // Add point to target pos resource
-    let target_pos = TargetPos(point, target, marker);
-    commands.insert_resource(target_pos);
-}
-
-fn aim_guideline(
-    target_pos: Option<Res<TargetPos>>,
-    char_query: Query<&GlobalTransform, With<Character>>,
-    mut guideline: ResMut<Guideline>,
+    grappleable_query: Query<&GlobalTransform, With<Grappleable>>,
+    target_query: Query<&Transform, Without<MarkerSprite>>,
+    mut marker_query: Query<
+        (&mut Transform, &mut Visibility, &mut Interpolated),
+        With<MarkerSprite>,
+    >,
     mut commands: Commands,
 ) {
-    // Clear old guidelines
-    remove_guideline(&mut guideline, &mut commands);
+    for (entity, state, transform, char, action_state, player, target_pos, marker) in
+        &mut char_query
+    {
+        if *state != GrappleState::Aiming {
+            continue;
+        }
 
-    let Some(target_pos) = target_pos else {
-        trace!("No target pos for grapple guidelines");
-        return;
-    };
-    let char = char_query.single();
-
-    // Get direction from character to target
-    let char_pos = char.translation().truncate();
-    let target_pos = target_pos.0;
-    let direction = target_pos - char_pos;
-    let distance = direction.normalize() * GUIDELINE_DISTANCE;
-
-    // Add guidelines
-    let mut current_pos = char_pos;
-    while current_pos.distance(target_pos) >= GUIDELINE_DISTANCE {
-        let sprite = SpriteBundle {
-            sprite: Sprite {
-                color: Color::BLUE,
-                custom_size: Some(Vec2::new(GUIDELINE_SIZE, GUIDELINE_SIZE)),
-                ..default()
-            },
-            transform: Transform {
-                translation: current_pos.extend(2.0),
-                ..default()
-            },
-            ..default()
+        let aimer = Aimer {
+            entity,
+            origin: transform.translation.truncate(),
+            range: char.grapple.range,
+            action_state,
+            uses_mouse: player.is_some_and(Player::uses_mouse),
+        };
+        let aimed_at = match cast_grapple_ray(
+            &aimer,
+            &rapier_context,
+            &window_query,
+            &camera_query,
+            &grappleable_query,
+        ) {
+            Ok(aim) => {
+                commands.entity(entity).insert(AimPreview {
+                    end: aim.point,
+                    valid: aim.target.is_some(),
+                });
+                aim.target.map(|target| (aim.point, target))
+            }
+            Err(_) => {
+                trace!("No result for grapple raycast");
+                commands.entity(entity).remove::<AimPreview>();
+                None
+            }
+        };
+        let aimed_at = aimed_at.and_then(|(point, target)| match target_query.get(target) {
+            Ok(target_transform) => Some((point, target, target_transform)),
+            Err(_) => {
+                error!("Could not get transform of grapple target {:?}", target);
+                None
+            }
+        });
+
+        let Some((point, target, target_transform)) = aimed_at else {
+            trace!("Grapple is not aimed at anything it can attach to");
+            if target_pos.is_some() {
+                commands.entity(entity).remove::<TargetPos>();
+            }
+            // Kept around in case the character aims back at something
+            if let Some(marker) = marker {
+                if let Ok((_, mut visibility, _)) = marker_query.get_mut(marker.0) {
+                    visibility.set_if_neq(Visibility::Hidden);
+                }
+            }
+            continue;
         };
 
-        let entity = commands.spawn(sprite).id();
+        let marker = match marker {
+            Some(marker) => {
+                if let Ok((mut marker_transform, mut visibility, mut interpolated)) =
+                    marker_query.get_mut(marker.0)
+                {
+                    marker_transform.translation.x = point.x;
+                    marker_transform.translation.y = point.y;
+                    // Jumps straight to the new target instead of sliding there
+                    *interpolated = Interpolated::new(point);
+                    visibility.set_if_neq(Visibility::Inherited);
+                }
+                marker.0
+            }
+            None => {
+                let marker = add_grapple_marker(&mut commands, &point);
+                commands.entity(entity).insert(Marker(marker));
+                marker
+            }
+        };
 
-        guideline.0.push(entity);
-        current_pos += distance;
+        // Add point to target pos
+        let new_target_pos = TargetPos::new(point, target, target_transform, marker);
+        match target_pos {
+            Some(mut target_pos) => *target_pos = new_target_pos,
+            None => {
+                commands.entity(entity).insert(new_target_pos);
+            }
+        }
     }
 }
 
-/// Cleanly removes the `TargetPos` resource.
+/// Cleanly removes a character's `TargetPos`.
 ///
-/// This despawns the marker entity and removes the resource.
-fn remove_target_pos(commands: &mut Commands, marker: Entity) {
+/// This despawns the marker entity and removes the components.
+fn remove_target_pos(commands: &mut Commands, char: Entity, marker: Entity) {
     trace!("Removing target pos for marker {:?}", marker);
     commands.entity(marker).despawn_recursive();
-    commands.remove_resource::<TargetPos>();
+    commands.entity(char).remove::<(TargetPos, Marker)>();
 }
 
 enum RaycastError {
-    NoCharacter,
     NoCamera,
     CouldNotResolveMousePos,
-    RayHitNothing,
+    NoAimDirection,
 }
 
-/// Casts a ray from the character to the mouse position.
-///
-/// # Returns
-///
-/// A tuple, containing:
+/// The character aiming the grapple.
+struct Aimer<'a> {
+    entity: Entity,
+    origin: Vec2,
+    /// In pixels
+    range: f32,
+    action_state: &'a ActionState<Action>,
+    /// Whether the mouse can be used to aim, when the stick/keys aren't.
+    uses_mouse: bool,
+}
+
+/// Where a grapple ray ended up.
+struct Aim {
+    /// Where the ray hit something, or the end of the grapple's range if it
+    /// didn't.
+    point: Vec2,
+    /// What the ray hit, if the grapple can attach to it.
+    target: Option<Entity>,
+}
+
+/// Casts a ray from the character in the direction being aimed, up to the
+/// grapple's range.
 ///
-/// 0. the point of impact
-/// 1. the entity that was impacted
+/// Sensors (eg coins) are ignored, but anything else blocks the ray even if
+/// the grapple can't attach to it.
 ///
-/// An error is returned if there was an error casting the ray, or if the ray
-/// hit nothing.
+/// An error is returned if there was an error casting the ray.
 fn cast_grapple_ray(
-    rapier_context: Res<RapierContext>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    char_query: Query<(Entity, &Transform), With<Character>>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-) -> Result<(Vec2, Entity), RaycastError> {
+    aimer: &Aimer,
+    rapier_context: &RapierContext,
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+    grappleable_query: &Query<&GlobalTransform, With<Grappleable>>,
+) -> Result<Aim, RaycastError> {
     // Resolve queries
     let window = window_query.single();
-    let Ok((char, char_transform)) = char_query.get_single() else {
-        error!("Could not get character entity or transform");
-        return Err(RaycastError::NoCharacter);
-    };
     let Ok((camera, camera_transform)) = camera_query.get_single() else {
         error!("Could not get camera for grapple raycast");
         return Err(RaycastError::NoCamera);
     };
 
-    // Get ray input
-    let Ok(direction) = resolve_mouse_pos(
-        window,
-        camera,
-        camera_transform,
-        char_transform.translation.truncate(),
-    ) else {
-        trace!("Could not resolve mouse position for starting grapple");
-        return Err(RaycastError::CouldNotResolveMousePos);
+    let origin = aimer.origin;
+    let range = aimer.range;
+    let query_filter = QueryFilter::new()
+        .exclude_sensors()
+        .exclude_collider(aimer.entity);
+    let cast = |direction: Vec2| {
+        cast_ray(
+            rapier_context,
+            grappleable_query,
+            origin,
+            direction,
+            range,
+            query_filter,
+        )
     };
-    let origin = char_transform.translation.truncate();
-    let distance_to_window_edge = get_distance_to_window_edge(char_transform, window, direction);
-    // FIXME: exclude character from raycast
-    let query_filter = QueryFilter {
-        exclude_collider: Some(char),
-        ..default()
+
+    // Get ray input, preferring the stick/keys over the mouse
+    let Some(direction) = resolve_aim_direction(aimer.action_state) else {
+        if !aimer.uses_mouse {
+            trace!("Not aiming the grapple in any direction");
+            return Err(RaycastError::NoAimDirection);
+        }
+        let Ok(direction) = resolve_mouse_pos(window, camera, camera_transform, origin) else {
+            trace!("Could not resolve mouse position for starting grapple");
+            return Err(RaycastError::CouldNotResolveMousePos);
+        };
+        return Ok(cast(direction));
     };
 
+    let aim = cast(direction);
+    if aim.target.is_some() {
+        return Ok(aim);
+    }
+
+    // Sticks and keys aren't very precise, so snap to the closest surface
+    // that is roughly in the right direction
+    let mut candidates = grappleable_query
+        .iter()
+        .map(|transform| transform.translation().truncate() - origin)
+        .filter(|offset| {
+            offset.length() <= range && offset.angle_between(direction).abs() <= AIM_ASSIST_ANGLE
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.length().total_cmp(&b.length()));
+
+    Ok(candidates
+        .into_iter()
+        .map(|offset| cast(offset.normalize()))
+        .find(|aim| aim.target.is_some())
+        .unwrap_or(aim))
+}
+
+fn cast_ray(
+    rapier_context: &RapierContext,
+    grappleable_query: &Query<&GlobalTransform, With<Grappleable>>,
+    origin: Vec2,
+    direction: Vec2,
+    range: f32,
+    query_filter: QueryFilter,
+) -> Aim {
     trace!(
-        "Origin: {}, direction: {}, distance_to_window_edge: {}",
+        "Origin: {}, direction: {}, range: {}",
         origin,
         direction,
-        distance_to_window_edge
+        range
     );
 
-    // Cast ray
-    let Some((entity, toi)) = rapier_context.cast_ray(
-        origin,
-        direction,
-        distance_to_window_edge,
-        true,
-        query_filter,
-    ) else {
+    let Some((entity, toi)) = rapier_context.cast_ray(origin, direction, range, true, query_filter)
+    else {
         trace!("Raycast hit nothing");
-        return Err(RaycastError::RayHitNothing);
+        return Aim {
+            point: origin + direction * range,
+            target: None,
+        };
     };
     let point = origin + direction * toi;
 
     trace!("Raycast hit entity {:?} at {:?}", entity, point);
 
-    Ok((point, entity))
+    Aim {
+        point,
+        target: grappleable_query.contains(entity).then_some(entity),
+    }
 }
 
 fn grapple(
-    action_state_query: Query<&ActionState<Action>, With<Character>>,
-    mut next_grapple_state: ResMut<NextState<GrappleState>>,
+    char_query: Query<(Entity, &GrappleState, &LatchedPresses), With<Character>>,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
 ) {
-    let action_state = action_state_query.single();
-    let just_pressed = action_state.get_just_pressed();
+    for (entity, state, latched) in &char_query {
+        if *state != GrappleState::Grappling {
+            continue;
+        }
 
-    // If the grapple key was just pressed, stop grappling and start aiming
-    if just_pressed.contains(&Action::Grapple) {
-        info!("Stopping grapple (grappling -> aiming)");
-        next_grapple_state.set(GrappleState::Aiming);
+        // If the grapple key was just pressed, stop grappling and start aiming
+        if latched.just_pressed(Action::Grapple) {
+            info!("Stopping grapple (grappling -> aiming)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Aiming,
+            });
+        }
     }
 }
 
+/// Moves the grapple point along with the target, in case the target moves.
+fn track_target(
+    mut char_query: Query<(Entity, &GrappleState, &mut TargetPos)>,
+    mut transform_query: Query<&mut Transform>,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
+) {
+    for (entity, state, mut target_pos) in &mut char_query {
+        if *state != GrappleState::Grappling {
+            continue;
+        }
+
+        let Ok(target_transform) = transform_query.get(target_pos.target) else {
+            info!("Ending grapple because the target is gone (grappling -> idle)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Grappling.next(),
+            });
+            continue;
+        };
+        let point = target_transform
+            .compute_affine()
+            .transform_point3(target_pos.local_point)
+            .truncate();
+        if point == target_pos.point {
+            continue;
+        }
+
+        trace!("Grapple target moved to {:?}", point);
+        target_pos.point = point;
+        if let Ok(mut marker_transform) = transform_query.get_mut(target_pos.marker) {
+            marker_transform.translation.x = point.x;
+            marker_transform.translation.y = point.y;
+        }
+    }
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
 fn manage_grapple(
-    char_query: Query<&GlobalTransform, With<Character>>,
-    target_pos: Option<ResMut<TargetPos>>,
-    char_controller_query: Query<&mut KinematicCharacterController, With<Character>>,
-    mut sprite_query: Query<&mut Sprite, With<Character>>,
+    mut char_query: Query<
+        (
+            &GrappleState,
+            &Transform,
+            &TargetPos,
+            &mut KinematicVelocity,
+            &mut Sprite,
+        ),
+        (With<Character>, Without<Swinging>, Without<PullingTarget>),
+    >,
 ) {
-    // Resolve queries
-    let Ok(char_transform) = char_query.get_single() else {
-        error!("Could not get character transform");
-        return;
-    };
-    let Some(target_pos) = target_pos else {
-        trace!("Could not get target pos resource");
-        return;
-    };
+    for (state, char_transform, target_pos, mut velocity, mut sprite) in &mut char_query {
+        if *state != GrappleState::Grappling {
+            continue;
+        }
 
-    let char = char_transform.translation().truncate();
-    let target = target_pos.0;
+        let char = char_transform.translation.truncate();
+        let target = target_pos.point;
 
-    // Recalculate the direction to the target
-    let direction = target - char;
-    let direction = direction.normalize();
+        // Recalculate the direction to the target
+        let direction = target - char;
+        let direction = direction.normalize();
 
-    trace!("Recalculated grapple direction to {:?}", direction);
+        trace!("Recalculated grapple direction to {:?}", direction);
 
-    sprite_query.single_mut().flip_x = direction.x < 0.0;
+        sprite.flip_x = direction.x < 0.0;
 
-    // Set the force on the character
-    add_grapple_force(char_controller_query, direction);
+        // Set the force on the character
+        add_grapple_force(&mut velocity, direction);
+    }
 }
 
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
 fn should_grapple_end(
-    player: Query<&KinematicCharacterControllerOutput, With<Player>>,
-    target_pos: Option<Res<TargetPos>>,
-    mut next_grapple_state: ResMut<NextState<GrappleState>>,
+    player_query: Query<
+        (
+            Entity,
+            &GrappleState,
+            &KinematicCharacterControllerOutput,
+            Option<&TargetPos>,
+            Has<Swinging>,
+            Has<PullingTarget>,
+        ),
+        With<Player>,
+    >,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
 ) {
-    let Ok(player) = player.get_single() else {
-        // FIXME: should never happen but does if the player is mid-grapple and dies,
-        //        then the restart button is pressed.
-        // In theory this shouldn't be possible. But it keeps happening (might be a bevy bug).
-        // I spent a ton of time on it, and couldn't figure it out. It's easier to just do this.
-        error!("Couldn't get player when testing for grapple end.");
-        return;
-    };
-    let Some(target_pos) = target_pos else {
-        trace!("No target pos resource");
+    for (entity, state, player, target_pos, swinging, pulling) in &player_query {
+        if *state != GrappleState::Grappling {
+            continue;
+        }
 
-        // End grapple
-        info!("Ending grapple because of missing target pos (grappling -> idle)");
-        next_grapple_state.set(GrappleState::Grappling.next());
+        let Some(target_pos) = target_pos else {
+            trace!("No target pos");
 
-        return;
-    };
-    let target = target_pos.1;
+            // End grapple
+            info!("Ending grapple because of missing target pos (grappling -> idle)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Grappling.next(),
+            });
+            continue;
+        };
+        let target = target_pos.target;
 
-    // Check if the character is touching the target
-    for collision in &player.collisions {
-        if collision.entity == target {
+        // Swinging into the target doesn't let go of the rope, and pulled
+        // targets are let go of by `pull_target`
+        if swinging || pulling {
+            continue;
+        }
+
+        // Check if the character is touching the target
+        if player
+            .collisions
+            .iter()
+            .any(|collision| collision.entity == target)
+        {
             // End grapple
             info!("Ending grapple because character is touching target (grappling -> idle)");
-            next_grapple_state.set(GrappleState::Grappling.next());
-            return;
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Grappling.next(),
+            });
+            continue;
         }
-    }
 
-    trace!("Character is not touching target ({:?})", target_pos.0);
+        trace!("Character is not touching target ({:?})", target_pos.point);
+    }
 }
 
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
 fn end_grapple_on_other_input(
-    action_state_query: Query<&ActionState<Action>, With<Character>>,
-    mut next_grapple_state: ResMut<NextState<GrappleState>>,
+    char_query: Query<
+        (Entity, &GrappleState, &ActionState<Action>),
+        // Swinging uses the other inputs
+        (With<Character>, Without<Swinging>),
+    >,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
 ) {
-    let action_state = action_state_query.single();
-    for action in action_state.get_pressed() {
-        if action != Action::Grapple {
+    for (entity, state, action_state) in &char_query {
+        if *state != GrappleState::Grappling {
+            continue;
+        }
+
+        let other_input = action_state.get_pressed().into_iter().any(|action| {
+            action != Action::Grapple && action != Action::Aim && action != Action::Attack
+        });
+        if other_input {
             // End grapple
             info!("Ending grapple because of input (grappling -> idle)");
-            next_grapple_state.set(GrappleState::Grappling.next());
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Grappling.next(),
+            });
         }
     }
 }
@@ -363,51 +739,115 @@ fn add_grapple_marker(commands: &mut Commands, point: &Vec2) -> Entity {
     // add a point at the hit location
     trace!("Adding grapple marker at {:?}", point);
     commands
-        .spawn(SpriteBundle {
-            sprite: Sprite {
-                color: Color::RED,
-                custom_size: Some(Vec2::new(10.0, 10.0)),
-                ..default()
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::GREEN,
+                    custom_size: Some(Vec2::new(10.0, 10.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(point.extend(z_index::GRAPPLE)),
+                ..Default::default()
             },
-            transform: Transform::from_translation(point.extend(1.0)),
-            ..Default::default()
-        })
+            MarkerSprite,
+            // Follows the target, so it should move as smoothly as it does
+            Interpolated::new(*point),
+            // Goes away with the level, in case the character dies mid-grapple
+            level::LevelEntity,
+        ))
         .id()
 }
 
-fn end_grapple(target_pos: Option<Res<TargetPos>>, mut commands: Commands) {
-    debug!("Ending grapple");
+/// The grapple takes over the character's vertical movement, so a jump in
+/// progress would fight with it.
+fn cancel_jump(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    char_query: Query<(), With<jump::JumpComponent>>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.to != GrappleState::Grappling || !char_query.contains(change.entity) {
+            continue;
+        }
 
-    // Remove target pos resource if it exists
-    if let Some(target_pos) = target_pos {
-        remove_target_pos(&mut commands, target_pos.2);
+        debug!("Cancelling jump because of grapple");
+        commands
+            .entity(change.entity)
+            .remove::<jump::JumpComponent>();
     }
 }
 
-fn remove_guideline(guideline: &mut ResMut<Guideline>, commands: &mut Commands) {
-    trace!("Removing guideline");
+fn end_grapple(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    marker_query: Query<&Marker>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        // Aiming can be cancelled (eg by dashing), so the marker needs to be
+        // removed without going through `Grappling`
+        let cancelled = change.from == GrappleState::Aiming && change.to == GrappleState::Idle;
+        if change.from != GrappleState::Grappling && !cancelled {
+            continue;
+        }
 
-    // Clear old guidelines
-    for guideline in &guideline.0 {
-        commands.entity(*guideline).despawn();
-    }
+        debug!("Ending grapple");
 
-    guideline.0.clear();
+        // Remove target pos if it exists
+        if let Ok(marker) = marker_query.get(change.entity) {
+            remove_target_pos(&mut commands, change.entity, marker.0);
+        }
+    }
 }
 
-fn remove_guideline_system(mut guideline: ResMut<Guideline>, mut commands: Commands) {
-    remove_guideline(&mut guideline, &mut commands);
+fn remove_aim_preview(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.from == GrappleState::Aiming {
+            commands.entity(change.entity).remove::<AimPreview>();
+        }
+    }
 }
 
-fn get_distance_to_window_edge(char: &Transform, window: &Window, direction: Vec2) -> f32 {
-    let window_size = Vec2::new(window.width(), window.height());
-    let char_pos = char.translation.truncate();
+fn start_cooldown(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    char_query: Query<&Character>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.from != GrappleState::Grappling {
+            continue;
+        }
+        let Ok(char) = char_query.get(change.entity) else {
+            continue;
+        };
 
-    let distance_to_edge = window_size - char_pos;
-    let distance_to_edge = distance_to_edge / direction;
-    let distance_to_edge = (distance_to_edge.x.powf(2.0) + distance_to_edge.y.powf(2.0)).sqrt();
+        trace!("Starting grapple cooldown");
+        commands.entity(change.entity).insert(GrappleCooldown {
+            remaining: char.grapple.cooldown,
+        });
+    }
+}
+
+fn tick_cooldown(
+    time: Res<Time>,
+    mut char_query: Query<(Entity, &mut GrappleCooldown)>,
+    mut commands: Commands,
+) {
+    for (entity, mut cooldown) in &mut char_query {
+        cooldown.remaining -= time.delta_seconds();
+        if cooldown.remaining <= 0.0 {
+            trace!("Grapple cooldown finished");
+            commands.entity(entity).remove::<GrappleCooldown>();
+        }
+    }
+}
 
-    distance_to_edge / 2.0
+/// The direction being aimed with a stick or keys, if there is one.
+fn resolve_aim_direction(action_state: &ActionState<Action>) -> Option<Vec2> {
+    let aim = action_state.axis_pair(&Action::Aim)?.xy();
+    (aim.length() >= AIM_DEADZONE).then(|| aim.normalize())
 }
 
 enum ResolveMousePosError {
@@ -0,0 +1,129 @@
+//! Grappling something light pulls it towards the character, instead of
+//! pulling the character towards it.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{KinematicCharacterController, Velocity};
+
+use crate::{plugins::in_game::components::character::Character, GRAVITY};
+
+use super::{GrappleState, GrappleStateChanged, SetGrappleState, TargetPos};
+
+/// How fast pulled things move towards the character, in pixels per second.
+const PULL_SPEED: f32 = 360.0;
+/// How close a pulled thing gets before the grapple lets go of it, in pixels.
+const PULL_STOP_DISTANCE: f32 = 96.0;
+
+/// How heavy something is, for deciding whether the grapple can pull it.
+///
+/// Things without a weight can't be pulled.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct Weight(pub f32);
+
+/// The character is pulling the grapple target towards them.
+#[derive(Component, Debug, Clone)]
+pub struct PullingTarget {
+    target: Entity,
+}
+
+/// The entity is being pulled towards a character by a grapple.
+#[derive(Component, Debug, Clone)]
+pub struct Pulled;
+
+/// Whether `char` can pull the grapple target, instead of being pulled.
+pub(super) fn can_pull(char: &Character, weight: Option<&Weight>) -> bool {
+    weight.is_some_and(|weight| weight.0 <= char.grapple.max_pull_weight)
+}
+
+pub(super) fn start_pulling(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    char_query: Query<(&Character, &TargetPos)>,
+    weight_query: Query<&Weight>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.to != GrappleState::Grappling {
+            continue;
+        }
+        let Ok((char, target_pos)) = char_query.get(change.entity) else {
+            continue;
+        };
+        let weight = weight_query.get(target_pos.target).ok();
+        if !can_pull(char, weight) {
+            continue;
+        }
+
+        debug!("Pulling grapple target {:?}", target_pos.target);
+        commands.entity(change.entity).insert(PullingTarget {
+            target: target_pos.target,
+        });
+        commands.entity(target_pos.target).insert(Pulled);
+    }
+}
+
+pub(super) fn stop_pulling(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    char_query: Query<&PullingTarget>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.from != GrappleState::Grappling {
+            continue;
+        }
+        let Ok(pulling) = char_query.get(change.entity) else {
+            continue;
+        };
+
+        commands.entity(change.entity).remove::<PullingTarget>();
+        // The target might have been despawned while it was being pulled
+        if let Some(mut target) = commands.get_entity(pulling.target) {
+            debug!("Letting go of pulled entity {:?}", pulling.target);
+            target.remove::<Pulled>();
+        }
+    }
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+pub(super) fn pull_target(
+    time: Res<Time>,
+    char_query: Query<(Entity, &Transform, &PullingTarget)>,
+    mut pulled_query: Query<
+        (
+            &Transform,
+            Option<&mut KinematicCharacterController>,
+            Option<&mut Velocity>,
+        ),
+        (With<Pulled>, Without<PullingTarget>),
+    >,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
+) {
+    for (entity, char_transform, pulling) in &char_query {
+        let Ok((transform, char_controller, velocity)) = pulled_query.get_mut(pulling.target)
+        else {
+            continue;
+        };
+
+        let offset = char_transform.translation.truncate() - transform.translation.truncate();
+        if offset.length() <= PULL_STOP_DISTANCE {
+            info!("Pulled target reached the character (grappling -> idle)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Grappling.next(),
+            });
+            continue;
+        }
+
+        let pull = offset.normalize() * PULL_SPEED;
+        trace!("Pulling target with velocity {:?}", pull);
+        // Kinematic things (eg enemies) are moved directly, and dynamic ones
+        // go through the physics engine so they can still be knocked around.
+        // Kinematic things don't fall on their own, so they still need
+        // gravity while they're pulled
+        if let Some(mut char_controller) = char_controller {
+            char_controller.translation = Some((pull + GRAVITY) * time.delta_seconds());
+        }
+        if let Some(mut velocity) = velocity {
+            velocity.linvel = pull;
+        }
+    }
+}
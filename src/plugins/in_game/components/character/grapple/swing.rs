@@ -0,0 +1,164 @@
+//! The rope grapple mode, where the character swings from the target like a
+//! pendulum instead of being pulled towards it.
+//!
+//! The rope only pulls (it never pushes), so the character falls freely while
+//! they are closer to the target than the rope is long.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::plugins::in_game::components::character::{
+    input::LatchedPresses, velocity::KinematicVelocity, Action, Character,
+};
+
+use super::{
+    pull::{can_pull, Weight},
+    GrappleMode, GrappleState, GrappleStateChanged, SetGrappleState, TargetPos,
+};
+
+/// In pixels
+const MAX_ROPE_LENGTH: f32 = 400.0;
+/// In pixels
+const MIN_ROPE_LENGTH: f32 = 48.0;
+/// How fast the rope is reeled in and out, in pixels per second.
+const REEL_SPEED: f32 = 240.0;
+/// How quickly pressing left or right speeds up the swing, in pixels per
+/// second, per second.
+const SWING_ACCELERATION: f32 = 600.0;
+
+/// The character is hanging from the grapple target by a rope.
+#[derive(Component, Debug, Clone)]
+pub struct Swinging {
+    /// In pixels
+    length: f32,
+}
+
+impl Swinging {
+    pub(super) fn length(&self) -> f32 {
+        self.length
+    }
+}
+
+/// Attaches the rope for characters that swing (instead of being pulled).
+pub(super) fn attach_rope(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    char_query: Query<(&Character, &Transform, &TargetPos)>,
+    weight_query: Query<&Weight>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.to != GrappleState::Grappling {
+            continue;
+        }
+        let Ok((char, transform, target_pos)) = char_query.get(change.entity) else {
+            continue;
+        };
+
+        // Light targets are pulled in instead
+        let weight = weight_query.get(target_pos.target).ok();
+        if char.abilities.grapple_mode != GrappleMode::Swing || can_pull(char, weight) {
+            continue;
+        }
+
+        let length = transform
+            .translation
+            .truncate()
+            .distance(target_pos.point)
+            .clamp(MIN_ROPE_LENGTH, MAX_ROPE_LENGTH);
+        debug!("Attaching rope with length {}", length);
+        commands.entity(change.entity).insert(Swinging { length });
+    }
+}
+
+pub(super) fn detach_rope(
+    mut changed_events: EventReader<GrappleStateChanged>,
+    char_query: Query<(), With<Swinging>>,
+    mut commands: Commands,
+) {
+    for change in changed_events.read() {
+        if change.from != GrappleState::Grappling || !char_query.contains(change.entity) {
+            continue;
+        }
+
+        debug!("Detaching rope");
+        commands.entity(change.entity).remove::<Swinging>();
+    }
+}
+
+pub(super) fn swing(
+    time: Res<Time>,
+    mut char_query: Query<(
+        &mut Swinging,
+        &TargetPos,
+        &Transform,
+        &ActionState<Action>,
+        &mut KinematicVelocity,
+        &mut Sprite,
+    )>,
+) {
+    let delta = time.delta_seconds();
+
+    for (mut swinging, target_pos, transform, action_state, mut velocity, mut sprite) in
+        &mut char_query
+    {
+        // Reel in and out
+        let mut reel = 0.0;
+        if action_state.pressed(&Action::ReelIn) {
+            reel -= 1.0;
+        }
+        if action_state.pressed(&Action::ReelOut) {
+            reel += 1.0;
+        }
+        swinging.length =
+            (swinging.length + reel * REEL_SPEED * delta).clamp(MIN_ROPE_LENGTH, MAX_ROPE_LENGTH);
+
+        // Pump the swing
+        let mut direction = 0.0;
+        if action_state.pressed(&Action::Left) {
+            direction -= 1.0;
+        }
+        if action_state.pressed(&Action::Right) {
+            direction += 1.0;
+        }
+        velocity.x += direction * SWING_ACCELERATION * delta;
+
+        // Keep the character within the rope's length. Gravity has already
+        // been applied, so removing the outwards part of the velocity while
+        // the rope is taut turns the fall into a swing.
+        let offset = transform.translation.truncate() - target_pos.point;
+        if offset.length() >= swinging.length {
+            let outwards = offset.normalize_or_zero();
+            let outwards_speed = velocity.dot(outwards);
+            if outwards_speed > 0.0 {
+                velocity.0 -= outwards * outwards_speed;
+            }
+        }
+        // Pull the character back in if they would end up past the end of the
+        // rope (eg because of reeling in)
+        let next_offset = offset + velocity.0 * delta;
+        let stretch = next_offset.length() - swinging.length;
+        if stretch > 0.0 {
+            velocity.0 -= next_offset.normalize_or_zero() * (stretch / delta).min(REEL_SPEED);
+        }
+
+        if velocity.x != 0.0 {
+            sprite.flip_x = velocity.x < 0.0;
+        }
+    }
+}
+
+/// Jumping lets go of the rope, keeping the swing's momentum.
+pub(super) fn let_go_on_jump(
+    char_query: Query<(Entity, &LatchedPresses), With<Swinging>>,
+    mut set_grapple_state: EventWriter<SetGrappleState>,
+) {
+    for (entity, latched) in &char_query {
+        if latched.just_pressed(Action::Jump) {
+            info!("Letting go of rope (grappling -> idle)");
+            set_grapple_state.send(SetGrappleState {
+                entity,
+                state: GrappleState::Idle,
+            });
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Drawing the grapple: the dotted aim preview and the rope.
+//!
+//! Both are made of sprites that are kept around and moved every frame
+//! (instead of being despawned and respawned), and hidden when they aren't
+//! needed.
+
+use bevy::prelude::*;
+
+use crate::{
+    plugins::in_game::{assets::ImageAssets, level},
+    z_index,
+};
+
+use super::{swing::Swinging, AimPreview, GrappleState, TargetPos};
+
+pub const ROPE_TEXTURE_PATH: &str = "rope.png";
+
+/// The gap between the dots of the aim preview, in pixels.
+const DOT_SPACING: f32 = 50.0;
+/// In pixels
+const DOT_SIZE: f32 = 10.0;
+const VALID_AIM_COLOR: Color = Color::BLUE;
+const INVALID_AIM_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.5);
+/// In pixels
+const ROPE_WIDTH: f32 = 6.0;
+/// How many straight pieces the rope is drawn with.
+const ROPE_SEGMENTS: usize = 16;
+/// How far segments overlap, so there are no gaps where the rope bends, in
+/// pixels.
+const ROPE_SEGMENT_OVERLAP: f32 = 1.0;
+
+/// A dot of the aim preview.
+#[derive(Component)]
+pub(super) struct GuidelineDot;
+
+/// A piece of the rope.
+#[derive(Component)]
+pub(super) struct RopeSegment;
+
+/// The dots of a character's aim preview, which are reused from frame to
+/// frame.
+#[derive(Component, Default)]
+pub(super) struct Guideline(Vec<Entity>);
+
+/// The pieces of a character's rope, which are reused from frame to frame.
+#[derive(Component, Default)]
+pub(super) struct Rope(Vec<Entity>);
+
+/// Moves the first `transforms.len()` entities of `pool` to `transforms`
+/// (spawning more with `spawn` if there aren't enough), and hides the rest.
+fn update_pool<M: Component>(
+    pool: &mut Vec<Entity>,
+    transforms: &[Transform],
+    query: &mut Query<(&mut Transform, &mut Visibility), With<M>>,
+    mut spawn: impl FnMut(Transform) -> Entity,
+) {
+    for (index, transform) in transforms.iter().enumerate() {
+        match pool.get(index) {
+            Some(entity) => {
+                if let Ok((mut pooled_transform, mut visibility)) = query.get_mut(*entity) {
+                    *pooled_transform = *transform;
+                    *visibility = Visibility::Inherited;
+                }
+            }
+            None => {
+                trace!("Growing sprite pool to {}", index + 1);
+                pool.push(spawn(*transform));
+            }
+        }
+    }
+
+    // The pool was grown to at least `transforms.len()` above
+    hide_pool(&pool[transforms.len()..], query);
+}
+
+/// Hides every entity in `pool`, without getting rid of them.
+fn hide_pool<M: Component>(
+    pool: &[Entity],
+    query: &mut Query<(&mut Transform, &mut Visibility), With<M>>,
+) {
+    for entity in pool {
+        if let Ok((_, mut visibility)) = query.get_mut(*entity) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+pub(super) fn aim_guideline(
+    mut char_query: Query<
+        (
+            &Transform,
+            &GrappleState,
+            Option<&AimPreview>,
+            &mut Guideline,
+        ),
+        Without<GuidelineDot>,
+    >,
+    mut dot_query: Query<(&mut Transform, &mut Visibility), With<GuidelineDot>>,
+    mut dot_sprite_query: Query<&mut Sprite, With<GuidelineDot>>,
+    mut commands: Commands,
+) {
+    for (char, state, aim_preview, mut guideline) in &mut char_query {
+        let Some(aim_preview) = aim_preview.filter(|_| *state == GrappleState::Aiming) else {
+            trace!("No aim preview for grapple guidelines");
+            hide_pool(&guideline.0, &mut dot_query);
+            continue;
+        };
+        let color = if aim_preview.valid {
+            VALID_AIM_COLOR
+        } else {
+            INVALID_AIM_COLOR
+        };
+
+        // Get direction from character to target
+        let char_pos = char.translation.truncate();
+        let direction = (aim_preview.end - char_pos).normalize_or_zero();
+        let dots = (char_pos.distance(aim_preview.end) / DOT_SPACING).floor() as usize;
+        let transforms: Vec<Transform> = (0..dots)
+            .map(|i| {
+                let pos = char_pos + direction * DOT_SPACING * (i as f32 + 1.0);
+                Transform::from_translation(pos.extend(z_index::GRAPPLE))
+            })
+            .collect();
+
+        update_pool(&mut guideline.0, &transforms, &mut dot_query, |transform| {
+            commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(Vec2::splat(DOT_SIZE)),
+                            ..default()
+                        },
+                        transform,
+                        ..default()
+                    },
+                    GuidelineDot,
+                    level::LevelEntity,
+                ))
+                .id()
+        });
+
+        for dot in &guideline.0 {
+            if let Ok(mut sprite) = dot_sprite_query.get_mut(*dot) {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+/// How far below the straight line the middle of a rope hangs.
+///
+/// Uses the parabola approximation of a hanging rope: a rope `length` long
+/// between points `span` apart sags by about `sqrt(3 * span * slack / 8)`.
+fn sag(span: f32, length: f32) -> f32 {
+    let slack = (length - span).max(0.0);
+    (3.0 * span * slack / 8.0).sqrt()
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+pub(super) fn draw_rope(
+    mut char_query: Query<
+        (
+            &Transform,
+            &GrappleState,
+            Option<&TargetPos>,
+            Option<&Swinging>,
+            &mut Rope,
+        ),
+        Without<RopeSegment>,
+    >,
+    marker_query: Query<&Transform, (Without<Rope>, Without<RopeSegment>)>,
+    mut segment_query: Query<(&mut Transform, &mut Visibility), With<RopeSegment>>,
+    image_assets: Res<ImageAssets>,
+    mut commands: Commands,
+) {
+    for (char, state, target_pos, swinging, mut rope) in &mut char_query {
+        let Some(target_pos) = target_pos.filter(|_| *state == GrappleState::Grappling) else {
+            hide_pool(&rope.0, &mut segment_query);
+            continue;
+        };
+
+        // The marker is interpolated, so it moves as smoothly as the character
+        let start = char.translation.truncate();
+        let end = marker_query
+            .get(target_pos.marker)
+            .map(|marker| marker.translation.truncate())
+            .unwrap_or(target_pos.point);
+
+        // Only a swinging rope can be slack, pulling keeps it taut
+        let span = start.distance(end);
+        let sag = swinging.map_or(0.0, |swinging| sag(span, swinging.length()));
+        let point = |t: f32| start.lerp(end, t) - Vec2::Y * sag * 4.0 * t * (1.0 - t);
+
+        let transforms: Vec<Transform> = (0..ROPE_SEGMENTS)
+            .map(|i| {
+                let from = point(i as f32 / ROPE_SEGMENTS as f32);
+                let to = point((i + 1) as f32 / ROPE_SEGMENTS as f32);
+                let offset = to - from;
+                Transform {
+                    translation: from.lerp(to, 0.5).extend(z_index::GRAPPLE),
+                    rotation: Quat::from_rotation_z(offset.y.atan2(offset.x)),
+                    // Segments are 1x1 sprites, so scale them to fit
+                    scale: Vec3::new(offset.length() + ROPE_SEGMENT_OVERLAP, ROPE_WIDTH, 1.0),
+                }
+            })
+            .collect();
+
+        update_pool(&mut rope.0, &transforms, &mut segment_query, |transform| {
+            commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::ONE),
+                            ..default()
+                        },
+                        texture: image_assets.rope.clone(),
+                        transform,
+                        ..default()
+                    },
+                    RopeSegment,
+                    level::LevelEntity,
+                ))
+                .id()
+        });
+    }
+}
@@ -0,0 +1,53 @@
+//! `ActionState` is updated once per frame, but characters move in
+//! `FixedUpdate`, which doesn't run every frame. A `just_pressed` checked
+//! there is lost whenever the frame it happened on had no tick, so presses
+//! (and releases) are latched every frame and kept until a tick has seen them.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::Action;
+
+/// The actions that were pressed or released since the last tick.
+#[derive(Component, Debug, Clone, Default)]
+pub struct LatchedPresses {
+    pressed: Vec<Action>,
+    released: Vec<Action>,
+}
+
+impl LatchedPresses {
+    /// Like `ActionState::just_pressed`, but for use in `FixedUpdate`.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Like `ActionState::just_released`, but for use in `FixedUpdate`.
+    pub fn just_released(&self, action: Action) -> bool {
+        self.released.contains(&action)
+    }
+}
+
+pub fn latch_presses(mut char_query: Query<(&ActionState<Action>, &mut LatchedPresses)>) {
+    for (action_state, mut latched) in &mut char_query {
+        for action in action_state.get_just_pressed() {
+            if !latched.pressed.contains(&action) {
+                trace!("Latching {:?} press", action);
+                latched.pressed.push(action);
+            }
+        }
+        for action in action_state.get_just_released() {
+            if !latched.released.contains(&action) {
+                trace!("Latching {:?} release", action);
+                latched.released.push(action);
+            }
+        }
+    }
+}
+
+/// Forgets the presses once a tick has had the chance to handle them.
+pub fn clear_presses(mut char_query: Query<&mut LatchedPresses>) {
+    for mut latched in &mut char_query {
+        latched.pressed.clear();
+        latched.released.clear();
+    }
+}
@@ -1,53 +1,185 @@
 //! Based off of <https://www.youtube.com/watch?v=hG9SzQxaCm8> and
 //! <https://www.youtube.com/watch?v=eeLPL3Y9jjA>.
 
-use std::time::Duration;
-
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::{KinematicCharacterController, KinematicCharacterControllerOutput};
+use bevy_rapier2d::prelude::KinematicCharacterControllerOutput;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{
+    input::LatchedPresses,
+    velocity::KinematicVelocity,
+    wall::{WallContact, WallJumpKick},
+    Action, Character,
+};
 
-use super::Character;
+/// How a character jumps. Each character can be tuned separately.
+#[derive(Clone, Debug, Default)]
+pub struct JumpSettings {
+    /// In pixels
+    pub height: f32,
+    /// In seconds
+    pub time_to_peak: f32,
+    /// How much stronger gravity is when jump is released on the way up.
+    ///
+    /// This is what makes short hops possible.
+    pub release_gravity_multiplier: f32,
+    /// How long after walking off a ledge the character can still jump, in
+    /// seconds.
+    pub coyote_time: f32,
+    /// How long a jump press is remembered for before landing, in seconds.
+    pub buffer_time: f32,
+}
 
 #[derive(Component, Debug, Clone, Default)]
 pub struct JumpComponent {
     gravity: f32,
     initial_velocity: f32,
+    release_gravity_multiplier: f32,
 
-    timer: Timer,
+    velocity: f32,
+    /// Whether jump was released on the way up.
+    released: bool,
 }
 
 impl JumpComponent {
-    pub fn new(height: f32, time_to_peak: f32) -> Self {
+    pub fn new(settings: &JumpSettings) -> Self {
+        let height = settings.height;
+        let time_to_peak = settings.time_to_peak;
+        let initial_velocity = 2.0 * height / time_to_peak;
+
         Self {
             gravity: -2.0 * height / (time_to_peak * time_to_peak),
-            initial_velocity: 2.0 * height / time_to_peak,
+            initial_velocity,
+            release_gravity_multiplier: settings.release_gravity_multiplier,
+
+            velocity: initial_velocity,
+            released: false,
+        }
+    }
+
+    pub fn is_rising(&self) -> bool {
+        self.velocity > 0.0
+    }
+
+    fn gravity(&self) -> f32 {
+        if self.released {
+            self.gravity * self.release_gravity_multiplier
+        } else {
+            self.gravity
+        }
+    }
+}
+
+/// Keeps track of the coyote time, jump buffer and air jumps for a character.
+#[derive(Component, Debug, Clone, Default)]
+pub struct JumpAssist {
+    /// Seconds left that the character can still jump after leaving the ground.
+    coyote_remaining: f32,
+    /// Seconds left that a jump press is remembered for.
+    buffer_remaining: f32,
+    /// Jumps left before the character has to touch the ground again.
+    air_jumps_remaining: u32,
+}
+
+pub fn update_jump_assist(
+    time: Res<Time>,
+    mut char_query: Query<(
+        &Character,
+        &mut JumpAssist,
+        &LatchedPresses,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+) {
+    for (char, mut assist, latched, output) in &mut char_query {
+        let delta = time.delta_seconds();
 
-            timer: Timer::from_seconds(time_to_peak * 2.0, TimerMode::Once),
+        if output.is_some_and(|output| output.grounded) {
+            assist.coyote_remaining = char.jump.coyote_time;
+            assist.air_jumps_remaining = char.abilities.air_jumps;
+        } else {
+            assist.coyote_remaining = (assist.coyote_remaining - delta).max(0.0);
+        }
+
+        if latched.just_pressed(Action::Jump) {
+            trace!("Jump pressed, buffering it.");
+            assist.buffer_remaining = char.jump.buffer_time;
+        } else {
+            assist.buffer_remaining = (assist.buffer_remaining - delta).max(0.0);
         }
     }
+}
 
-    pub fn velocity_at(&self, elapsed: Duration) -> f32 {
-        self.gravity * elapsed.as_secs_f32() + self.initial_velocity
+/// Starts a jump if one is buffered and the character can jump.
+///
+/// In order of preference, the character can jump if they are (or very
+/// recently were) on the ground, if they are touching a wall, or if they have
+/// air jumps left.
+pub fn start_jump(
+    mut char_query: Query<(Entity, &Character, &mut JumpAssist, &WallContact)>,
+    mut commands: Commands,
+) {
+    for (entity, char, mut assist, wall_contact) in &mut char_query {
+        if assist.buffer_remaining <= 0.0 {
+            continue;
+        }
+
+        let wall_side = wall_contact.side.filter(|_| char.abilities.wall_jump);
+        if assist.coyote_remaining > 0.0 {
+            info!("Character can jump, starting jump.");
+            assist.coyote_remaining = 0.0;
+        } else if let Some(wall_side) = wall_side {
+            info!("Character is touching a wall, starting wall jump.");
+            commands
+                .entity(entity)
+                .insert(WallJumpKick::away_from(wall_side));
+        } else if assist.air_jumps_remaining > 0 {
+            info!(
+                "Character is in the air, starting air jump ({} left).",
+                assist.air_jumps_remaining - 1
+            );
+            assist.air_jumps_remaining -= 1;
+        } else {
+            trace!("Jump is buffered, but character can't jump.");
+            continue;
+        }
+
+        assist.buffer_remaining = 0.0;
+
+        // Replaces the current jump if there is one
+        commands
+            .entity(entity)
+            .insert(JumpComponent::new(&char.jump));
     }
 }
 
 pub fn jump(
     time: Res<Time>,
-    mut jump_component_query: Query<(&mut JumpComponent, &mut KinematicCharacterController)>,
+    mut jump_component_query: Query<(
+        &mut JumpComponent,
+        &mut KinematicVelocity,
+        &ActionState<Action>,
+    )>,
 ) {
-    for (mut jump_component, mut char_controller) in &mut jump_component_query {
-        // Tick timer
-        jump_component.timer.tick(time.delta());
+    for (mut jump_component, mut velocity, action_state) in &mut jump_component_query {
+        // Short hop
+        if !jump_component.released
+            && jump_component.velocity > 0.0
+            && !action_state.pressed(&Action::Jump)
+        {
+            debug!("Jump released early, cutting jump short.");
+            jump_component.released = true;
+        }
 
         // Calculate velocity
-        let elapsed = jump_component.timer.elapsed();
-        let vel = jump_component.velocity_at(elapsed);
+        let gravity = jump_component.gravity();
+        let vel = jump_component.velocity + gravity * time.delta_seconds();
+        // Don't fall faster than the jump started
+        let vel = vel.max(-jump_component.initial_velocity);
+        jump_component.velocity = vel;
         debug!("Jumping w/ vel {}", vel);
 
         // Apply velocity
-        let mut trans = char_controller.translation.unwrap_or_default();
-        trans.y = vel;
-        char_controller.translation = Some(trans);
+        velocity.y = vel;
     }
 }
 
@@ -55,13 +187,13 @@ pub fn jump(
 #[allow(clippy::type_complexity)]
 pub fn stop_jump(
     // ALLOW: pretty simple query
-    mut char_query: Query<
+    char_query: Query<
         (Entity, &KinematicCharacterControllerOutput),
         (With<Character>, With<JumpComponent>),
     >,
     mut commands: Commands,
 ) {
-    if let Ok((entity, char_controller_output)) = char_query.get_single_mut() {
+    for (entity, char_controller_output) in &char_query {
         if char_controller_output.grounded {
             info!("Character is grounded, stopping jump.");
             commands.entity(entity).remove::<JumpComponent>();
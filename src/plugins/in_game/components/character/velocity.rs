@@ -0,0 +1,83 @@
+//! Characters move by changing their velocity, which is integrated into the
+//! character controller once per tick by `apply_velocity`. This way walking,
+//! jumping, grappling and knockback all build on (instead of overwriting) each
+//! other's momentum.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{KinematicCharacterController, KinematicCharacterControllerOutput};
+
+use crate::{FALL_ACCELERATION, GRAVITY};
+
+use super::{dash::Dashing, jump::JumpComponent, Character};
+
+/// How fast a character is moving, in pixels per second.
+#[derive(Component, Debug, Clone, Copy, Default, Deref, DerefMut)]
+pub struct KinematicVelocity(pub Vec2);
+
+/// How quickly a character speeds up and slows down.
+///
+/// All of these are in pixels per second, per second.
+#[derive(Clone, Debug, Default)]
+pub struct MovementSettings {
+    pub ground_acceleration: f32,
+    pub air_acceleration: f32,
+    pub ground_friction: f32,
+    pub air_friction: f32,
+}
+
+impl MovementSettings {
+    /// How quickly the character's horizontal speed changes.
+    ///
+    /// Characters speed up when there is input, and slow down (because of
+    /// friction) when there isn't.
+    pub fn rate(&self, grounded: bool, has_input: bool) -> f32 {
+        match (grounded, has_input) {
+            (true, true) => self.ground_acceleration,
+            (false, true) => self.air_acceleration,
+            (true, false) => self.ground_friction,
+            (false, false) => self.air_friction,
+        }
+    }
+}
+
+/// Moves `current` towards `target`, by at most `max_delta`.
+pub fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
+    if (target - current).abs() <= max_delta {
+        target
+    } else {
+        current + (target - current).signum() * max_delta
+    }
+}
+
+/// Makes characters fall when they aren't jumping or dashing (which have their
+/// own vertical movement).
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+pub fn apply_gravity(
+    time: Res<Time>,
+    mut char_query: Query<
+        (
+            &mut KinematicVelocity,
+            Option<&KinematicCharacterControllerOutput>,
+        ),
+        (With<Character>, Without<JumpComponent>, Without<Dashing>),
+    >,
+) {
+    for (mut velocity, output) in &mut char_query {
+        if output.is_some_and(|output| output.grounded) && velocity.y < 0.0 {
+            // Standing on the ground, so don't build up speed
+            velocity.y = 0.0;
+        }
+
+        velocity.y = (velocity.y - FALL_ACCELERATION * time.delta_seconds()).max(GRAVITY.y);
+    }
+}
+
+pub fn apply_velocity(
+    time: Res<Time>,
+    mut char_query: Query<(&KinematicVelocity, &mut KinematicCharacterController)>,
+) {
+    for (velocity, mut char_controller) in &mut char_query {
+        char_controller.translation = Some(velocity.0 * time.delta_seconds());
+    }
+}
@@ -0,0 +1,132 @@
+//! Wall sliding and wall jumping.
+//!
+//! Walls are detected from the character controller's collisions, so the
+//! character only "touches" a wall while they are moving into it.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::KinematicCharacterControllerOutput;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{jump::JumpComponent, velocity::KinematicVelocity, Action, Character};
+
+/// How fast the character can fall while sliding down a wall, in pixels per
+/// second.
+const WALL_SLIDE_SPEED: f32 = 120.0;
+/// How fast the character is pushed away from the wall when wall jumping, in
+/// pixels per second.
+const WALL_JUMP_KICK_SPEED: f32 = 360.0;
+/// How long the character is pushed away from the wall for, in seconds.
+const WALL_JUMP_KICK_TIME: f32 = 0.15;
+/// How horizontal a collision normal has to be to count as a wall.
+const MIN_WALL_NORMAL_X: f32 = 0.7;
+
+/// Which side of the character a wall is on, if any.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct WallContact {
+    /// `-1.0` for a wall on the left, `1.0` for a wall on the right.
+    pub side: Option<f32>,
+}
+
+/// Pushes the character away from a wall after a wall jump.
+#[derive(Component, Debug, Clone)]
+pub struct WallJumpKick {
+    direction: f32,
+    remaining: f32,
+}
+
+impl WallJumpKick {
+    /// Kick away from a wall on `wall_side`.
+    pub fn away_from(wall_side: f32) -> Self {
+        WallJumpKick {
+            direction: -wall_side,
+            remaining: WALL_JUMP_KICK_TIME,
+        }
+    }
+}
+
+pub fn detect_walls(
+    mut char_query: Query<(
+        &mut WallContact,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+) {
+    for (mut wall_contact, output) in &mut char_query {
+        let Some(output) = output else {
+            continue;
+        };
+
+        // Walls don't count while standing on the ground
+        let side = if output.grounded {
+            None
+        } else {
+            output.collisions.iter().find_map(|collision| {
+                // `normal1` is on the character, so it points towards the wall
+                let normal = collision.toi.normal1;
+                (normal.x.abs() >= MIN_WALL_NORMAL_X).then(|| normal.x.signum())
+            })
+        };
+
+        if side != wall_contact.side {
+            trace!("Wall contact changed to {:?}", side);
+            wall_contact.side = side;
+        }
+    }
+}
+
+pub fn wall_slide(
+    mut char_query: Query<(
+        &Character,
+        &WallContact,
+        &ActionState<Action>,
+        &mut KinematicVelocity,
+        Option<&JumpComponent>,
+    )>,
+) {
+    for (char, wall_contact, action_state, mut velocity, jump) in &mut char_query {
+        if !char.abilities.wall_slide {
+            continue;
+        }
+        let Some(side) = wall_contact.side else {
+            continue;
+        };
+
+        // Only slide while pressing into the wall
+        let pressing_into_wall = if side < 0.0 {
+            action_state.pressed(&Action::Left)
+        } else {
+            action_state.pressed(&Action::Right)
+        };
+        // Don't slow down the character on the way up
+        let rising = jump.is_some_and(JumpComponent::is_rising);
+        if !pressing_into_wall || rising {
+            continue;
+        }
+
+        if velocity.y < -WALL_SLIDE_SPEED {
+            trace!("Sliding down wall");
+            velocity.y = -WALL_SLIDE_SPEED;
+        }
+    }
+}
+
+pub fn wall_jump_kick(
+    time: Res<Time>,
+    mut char_query: Query<(
+        Entity,
+        &mut WallJumpKick,
+        &mut KinematicVelocity,
+        &mut Sprite,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut kick, mut velocity, mut sprite) in &mut char_query {
+        velocity.x = kick.direction * WALL_JUMP_KICK_SPEED;
+        sprite.flip_x = kick.direction < 0.0;
+
+        kick.remaining -= time.delta_seconds();
+        if kick.remaining <= 0.0 {
+            trace!("Wall jump kick finished");
+            commands.entity(entity).remove::<WallJumpKick>();
+        }
+    }
+}
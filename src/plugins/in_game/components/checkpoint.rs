@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_rapier2d::plugin::RapierContext;
+
+use crate::plugins::in_game::{bundles::checkpoint::Checkpoint, player::Player, InGameSet};
+
+const REACHED_COLOR: Color = Color::rgb(0.3, 0.85, 0.3);
+
+pub fn checkpoint_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (reach_checkpoint, show_reached_checkpoint).in_set(InGameSet),
+    );
+}
+
+/// Where the player respawns after dying, instead of the start of the level.
+///
+/// This is removed whenever a level is loaded.
+#[derive(Resource, Copy, Clone, Debug)]
+pub struct ActiveCheckpoint(pub Vec2);
+
+fn reach_checkpoint(
+    rapier_context: Res<RapierContext>,
+    player_query: Query<Entity, With<Player>>,
+    checkpoint_query: Query<(Entity, &Transform), With<Checkpoint>>,
+    active_checkpoint: Option<Res<ActiveCheckpoint>>,
+    mut commands: Commands,
+) {
+    for player in &player_query {
+        for (checkpoint, transform) in &checkpoint_query {
+            let position = transform.translation.truncate();
+            let already_active = active_checkpoint
+                .as_ref()
+                .is_some_and(|active| active.0 == position);
+            if already_active || rapier_context.intersection_pair(player, checkpoint) != Some(true)
+            {
+                continue;
+            }
+
+            info!("Reached checkpoint at {:?}", position);
+            commands.insert_resource(ActiveCheckpoint(position));
+        }
+    }
+}
+
+fn show_reached_checkpoint(
+    active_checkpoint: Option<Res<ActiveCheckpoint>>,
+    mut checkpoint_query: Query<(&Transform, &mut Sprite), With<Checkpoint>>,
+) {
+    let Some(active_checkpoint) = active_checkpoint else {
+        return;
+    };
+
+    for (transform, mut sprite) in &mut checkpoint_query {
+        if transform.translation.truncate() == active_checkpoint.0 {
+            sprite.color = REACHED_COLOR;
+        }
+    }
+}
@@ -2,59 +2,102 @@ use bevy::prelude::*;
 use bevy_rapier2d::plugin::RapierContext;
 
 use crate::{
-    plugins::in_game::{bundles::coin::Coin, level, player::Player, InGameSet},
+    plugins::in_game::{
+        bundles::coin::Coin,
+        level,
+        player::{self, Player, PlayerCount},
+        InGameSet,
+    },
     state::GameState,
 };
 
 const TEXT_POS: Vec2 = Vec2::new(10.0, 10.0);
 const FONT_SIZE: f32 = 64.0;
+/// How fast magnetised coins move towards the collector, in pixels per second.
+const MAGNET_SPEED: f32 = 480.0;
 
 pub fn coin_plugin(app: &mut App) {
     app.add_systems(OnEnter(GameState::InGame), create_text)
         .add_systems(
             Update,
-            (update_coin_score, coin_collisions).in_set(InGameSet),
+            (update_coin_score, (attract_coins, coin_collisions).chain()).in_set(InGameSet),
         );
 }
 
 #[derive(Component, Debug, Default)]
 pub struct CoinCollector {
     num_coins: u32,
+    /// How close coins have to be to get pulled in, in pixels.
+    pub magnet_radius: f32,
 }
 
+/// Shows how many coins a player has.
 #[derive(Component)]
-struct CoinScoreText;
+struct CoinScoreText {
+    /// The `Player::index` of the player.
+    player: usize,
+}
 
-fn create_text(mut commands: Commands) {
+fn create_text(mut commands: Commands, player_count: Res<PlayerCount>) {
     info!("Creating score text.");
 
-    commands
-        .spawn(TextBundle {
-            style: Style {
-                top: Val::Px(TEXT_POS.x),
-                left: Val::Px(TEXT_POS.y),
-                ..default()
-            },
-            text: Text::from_section(
-                "-",
-                TextStyle {
-                    font_size: FONT_SIZE,
+    for index in 0..player_count.0 {
+        commands
+            .spawn(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(TEXT_POS.x),
+                    left: Val::Px(TEXT_POS.y + player::hud_offset(index)),
                     ..default()
                 },
-            ),
-            ..default()
-        })
-        .insert(CoinScoreText)
-        .insert(level::LevelEntity);
+                text: Text::from_section(
+                    "-",
+                    TextStyle {
+                        font_size: FONT_SIZE,
+                        color: player::color(index),
+                        ..default()
+                    },
+                ),
+                ..default()
+            })
+            .insert(CoinScoreText { player: index })
+            .insert(level::LevelEntity);
+    }
 }
 
 fn update_coin_score(
-    player: Query<&CoinCollector, With<Player>>,
-    mut score_text: Query<&mut Text, With<CoinScoreText>>,
+    player_query: Query<(&Player, &CoinCollector)>,
+    mut score_text_query: Query<(&CoinScoreText, &mut Text)>,
 ) {
-    let player = player.single();
-    let mut score_text = score_text.single_mut();
-    score_text.sections[0].value = player.num_coins.to_string();
+    for (player, collector) in &player_query {
+        for (score_text, mut text) in &mut score_text_query {
+            if score_text.player == player.index {
+                text.sections[0].value = collector.num_coins.to_string();
+            }
+        }
+    }
+}
+
+fn attract_coins(
+    time: Res<Time>,
+    collectors: Query<(&Transform, &CoinCollector), Without<Coin>>,
+    mut coins: Query<&mut Transform, With<Coin>>,
+) {
+    for (collector_transform, collector) in &collectors {
+        if collector.magnet_radius <= 0.0 {
+            continue;
+        }
+
+        let target = collector_transform.translation.truncate();
+        for mut transform in &mut coins {
+            let offset = target - transform.translation.truncate();
+            if offset.length() > collector.magnet_radius {
+                continue;
+            }
+            let step = offset.clamp_length_max(MAGNET_SPEED * time.delta_seconds());
+            transform.translation += step.extend(0.0);
+        }
+    }
 }
 
 fn coin_collisions(
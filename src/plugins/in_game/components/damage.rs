@@ -1,54 +1,288 @@
+//! Anything with `Health` can be hurt by sending a `DamageEvent` at it.
+//!
+//! Touching something with a `Damage` component sends one automatically,
+//! unless the toucher landed on top of it, in which case it gets stomped
+//! instead.
+
 use std::collections::HashMap;
 
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::plugins::in_game::{bundles::enemy::Enemy, player::Player, InGameSet};
+use crate::plugins::in_game::InGameSet;
+
+use super::{
+    character::{
+        jump::{self, JumpComponent},
+        velocity::{self, KinematicVelocity},
+    },
+    health::Health,
+    invulnerable::{self, Invulnerable},
+};
 
-use super::health::Health;
+/// How long characters can't be hurt again after being hit, in seconds.
+const HIT_INVULNERABILITY_TIME: f32 = 1.0;
+/// How fast characters are knocked away from whatever hit them, in pixels per
+/// second.
+const KNOCKBACK_VELOCITY: Vec2 = Vec2::new(360.0, 300.0);
+/// How far up (from 0 to 1) the damage dealer's side of a contact has to face
+/// for it to count as landing on top of it.
+const STOMP_MIN_NORMAL_Y: f32 = 0.7;
+/// How much health something loses when it is landed on.
+const STOMP_DAMAGE: f32 = 100.0;
+/// How fast characters bounce up off something they land on, in pixels per
+/// second.
+const STOMP_BOUNCE_SPEED: f32 = 420.0;
 
 pub fn damage_plugin(app: &mut App) {
-    app.add_systems(Update, enemy_damage_player.in_set(InGameSet));
+    app.add_event::<DamageEvent>().add_systems(
+        FixedUpdate,
+        (contact_damage, (apply_damage, bounce_off_stomps))
+            .chain()
+            .in_set(DamageSet)
+            .in_set(InGameSet)
+            // Contacts are read from the latest physics step, and knockback
+            // and stomp bounces replace the jump's velocity, so they have to
+            // come after it (the jump is only removed once commands are
+            // applied) but before the velocity is used
+            .after(PhysicsSet::Writeback)
+            .after(jump::jump)
+            .before(velocity::apply_velocity),
+    );
 }
 
+/// Where damage is dealt, so deaths can be checked for afterwards.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DamageSet;
+
+/// Hurts anything with `Health` that touches this.
 #[derive(Component, Debug)]
 pub struct Damage(pub f32);
 
-pub fn enemy_damage_player(
-    mut player: Query<(Entity, &KinematicCharacterControllerOutput, &mut Health), With<Player>>,
-    enemies: Query<(Entity, &KinematicCharacterControllerOutput, &Damage), With<Enemy>>,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DamageKind {
+    /// Touching something with a `Damage` component.
+    Contact,
+    /// Being landed on.
+    Stomp,
+    /// Being hit by a spit bubble.
+    Projectile,
+}
+
+/// Asks for `target` to lose `amount` health.
+#[derive(Event, Debug)]
+pub struct DamageEvent {
+    /// Whatever did the damage.
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+}
+
+/// How much of each kind of damage is ignored, from 0 (none of it) to 1 (all
+/// of it).
+#[derive(Component, Clone, Debug, Default)]
+pub struct Resistances(pub HashMap<DamageKind, f32>);
+
+impl Resistances {
+    fn get(&self, kind: DamageKind) -> f32 {
+        self.0.get(&kind).copied().unwrap_or(0.0).clamp(0.0, 1.0)
+    }
+}
+
+/// Scales all of the damage something takes, after resistances.
+#[derive(Component, Clone, Debug)]
+pub struct DamageIntake(pub f32);
+
+impl Default for DamageIntake {
+    fn default() -> Self {
+        DamageIntake(1.0)
+    }
+}
+
+/// How a character touched something with `Damage`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Contact {
+    /// The character landed on top of it.
+    Stomp,
+    /// Anything else, which hurts the character.
+    Side,
+}
+
+impl Contact {
+    /// `normal` is the damage dealer's outwards normal where they touched.
+    fn from_normal(normal: Vec2) -> Self {
+        if normal.y >= STOMP_MIN_NORMAL_Y {
+            Contact::Stomp
+        } else {
+            Contact::Side
+        }
+    }
+
+    /// Landing on something counts, even if a corner was touched first.
+    fn or(self, other: Contact) -> Contact {
+        if self == Contact::Stomp || other == Contact::Stomp {
+            Contact::Stomp
+        } else {
+            Contact::Side
+        }
+    }
+}
+
+/// Sends damage events for things with `Damage` touching things with
+/// `Health`, from either side's character controller.
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+fn contact_damage(
+    dealer_query: Query<(Entity, &Damage, Option<&KinematicCharacterControllerOutput>)>,
+    target_query: Query<
+        (
+            Entity,
+            Option<&KinematicCharacterControllerOutput>,
+            Has<KinematicVelocity>,
+        ),
+        With<Health>,
+    >,
+    mut damage_events: EventWriter<DamageEvent>,
 ) {
-    let Ok((player_entity, player_char_controller, mut player_health)) = player.get_single_mut()
-    else {
-        // Player not created yet
-        trace!("Player not created yet. Skipping damage system.");
-        return;
+    // Keyed by (dealer, target)
+    let mut contacts = HashMap::new();
+    let mut add_contact = |dealer: Entity, target: Entity, contact: Contact| {
+        if dealer == target {
+            return;
+        }
+        trace!("{:?} touched {:?} ({:?})", target, dealer, contact);
+        contacts
+            .entry((dealer, target))
+            .and_modify(|existing: &mut Contact| *existing = existing.or(contact))
+            .or_insert(contact);
     };
 
-    let mut damages = HashMap::new();
-
-    // Iterate over all collisions with the player
-    for collision in &player_char_controller.collisions {
-        for (enemy_entity, _enemy_char_controller, damage) in &enemies {
-            if collision.entity == enemy_entity {
-                damages.insert(enemy_entity, damage);
-                trace!("player hit enemy, applying {:?} damage", damage);
+    // The normal is on the dealer's side, so it points up when the target
+    // lands on top of it
+    for (target, output, _) in &target_query {
+        for collision in output.iter().flat_map(|output| &output.collisions) {
+            if dealer_query.contains(collision.entity) {
+                add_contact(
+                    collision.entity,
+                    target,
+                    Contact::from_normal(collision.toi.normal2),
+                );
             }
         }
     }
-
-    // Iterate over all collisions with the enemies
-    for (enemy_entity, enemy_char_controller, damage) in &enemies {
-        for collision in &enemy_char_controller.collisions {
-            if collision.entity == player_entity {
-                damages.insert(enemy_entity, damage);
-                trace!("enemy hit player, applying {:?} damage", damage);
+    for (dealer, _, output) in &dealer_query {
+        for collision in output.iter().flat_map(|output| &output.collisions) {
+            if target_query.contains(collision.entity) {
+                add_contact(
+                    dealer,
+                    collision.entity,
+                    Contact::from_normal(collision.toi.normal1),
+                );
             }
         }
     }
 
-    // Apply the damages
-    for damage in damages.values() {
-        player_health.remaining -= damage.0;
+    for ((dealer, target), contact) in contacts {
+        let Ok((_, damage, _)) = dealer_query.get(dealer) else {
+            continue;
+        };
+        // Only characters (which can bounce off) can stomp
+        let can_stomp = target_query
+            .get(target)
+            .is_ok_and(|(_, _, has_velocity)| has_velocity);
+
+        if contact == Contact::Stomp && can_stomp {
+            damage_events.send(DamageEvent {
+                source: target,
+                target: dealer,
+                amount: STOMP_DAMAGE,
+                kind: DamageKind::Stomp,
+            });
+        } else {
+            damage_events.send(DamageEvent {
+                source: dealer,
+                target,
+                amount: damage.0,
+                kind: DamageKind::Contact,
+            });
+        }
+    }
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+fn apply_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut target_query: Query<(
+        &mut Health,
+        Option<&Resistances>,
+        Option<&DamageIntake>,
+        Has<Invulnerable>,
+        Option<&mut KinematicVelocity>,
+    )>,
+    transform_query: Query<&Transform>,
+    mut commands: Commands,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, resistances, intake, invulnerable, velocity)) =
+            target_query.get_mut(event.target)
+        else {
+            continue;
+        };
+        if invulnerable {
+            trace!("{:?} is invulnerable, ignoring {:?}", event.target, event);
+            continue;
+        }
+
+        let resistance = resistances.map_or(0.0, |resistances| resistances.get(event.kind));
+        let intake = intake.map_or(1.0, |intake| intake.0);
+        let amount = event.amount * (1.0 - resistance) * intake;
+        if amount <= 0.0 {
+            continue;
+        }
+        health.remaining -= amount;
+        debug!(
+            "{:?} took {} {:?} damage from {:?}, {} health remaining",
+            event.target, amount, event.kind, event.source, health.remaining
+        );
+
+        // Characters are knocked away from whatever hit them, and given a
+        // moment to recover before they can be hit again
+        let Some(mut velocity) = velocity else {
+            continue;
+        };
+        let away = match (
+            transform_query.get(event.target),
+            transform_query.get(event.source),
+        ) {
+            (Ok(target), Ok(source)) => target.translation.x - source.translation.x,
+            _ => 0.0,
+        };
+        let direction = if away < 0.0 { -1.0 } else { 1.0 };
+        velocity.0 = Vec2::new(direction * KNOCKBACK_VELOCITY.x, KNOCKBACK_VELOCITY.y);
+        commands.entity(event.target).remove::<JumpComponent>();
+        invulnerable::make_invulnerable(
+            &mut commands,
+            event.target,
+            Invulnerable::flashing(HIT_INVULNERABILITY_TIME),
+        );
+    }
+}
+
+/// Characters bounce up off whatever they stomp on.
+fn bounce_off_stomps(
+    mut damage_events: EventReader<DamageEvent>,
+    mut velocity_query: Query<&mut KinematicVelocity>,
+    mut commands: Commands,
+) {
+    for event in damage_events.read() {
+        if event.kind != DamageKind::Stomp {
+            continue;
+        }
+        if let Ok(mut velocity) = velocity_query.get_mut(event.source) {
+            velocity.y = STOMP_BOUNCE_SPEED;
+            commands.entity(event.source).remove::<JumpComponent>();
+        }
     }
 }
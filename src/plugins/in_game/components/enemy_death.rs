@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::plugins::in_game::{bundles::enemy::Enemy, InGameSet};
+
+use super::{
+    character::grapple::Grappleable,
+    damage::Damage,
+    health::{self, Died},
+};
+
+/// How long the death effect lasts, in seconds.
+const DEATH_TIME: f32 = 0.4;
+/// How much wider (as a multiple) enemies get while they are squashed.
+const SQUASH_WIDTH: f32 = 1.5;
+
+pub fn enemy_death_plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        kill_enemies.after(health::check_deaths).in_set(InGameSet),
+    )
+    .add_systems(Update, play_death_effect.in_set(InGameSet));
+}
+
+/// The enemy has been killed, and is squashing and fading away before it is
+/// despawned.
+#[derive(Component, Debug)]
+pub struct Dying {
+    remaining: f32,
+    /// The scale the enemy had before it died.
+    scale: Vec3,
+}
+
+/// Makes enemies that have died harmless and starts their death effect.
+fn kill_enemies(
+    mut died_events: EventReader<Died>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    mut commands: Commands,
+) {
+    for Died { entity } in died_events.read() {
+        let entity = *entity;
+        let Ok(transform) = enemy_query.get(entity) else {
+            continue;
+        };
+
+        info!("Enemy {:?} died", entity);
+        commands
+            .entity(entity)
+            .remove::<(
+                Enemy,
+                Damage,
+                Collider,
+                KinematicCharacterController,
+                Grappleable,
+            )>()
+            .insert(Dying {
+                remaining: DEATH_TIME,
+                scale: transform.scale,
+            });
+    }
+}
+
+fn play_death_effect(
+    time: Res<Time>,
+    mut dying_query: Query<(Entity, &mut Dying, &mut Transform, &mut Sprite)>,
+    mut commands: Commands,
+) {
+    for (entity, mut dying, mut transform, mut sprite) in &mut dying_query {
+        dying.remaining -= time.delta_seconds();
+        if dying.remaining <= 0.0 {
+            debug!("Despawning dead enemy {:?}", entity);
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Squash flat and fade out
+        let left = dying.remaining / DEATH_TIME;
+        transform.scale =
+            dying.scale * Vec3::new(SQUASH_WIDTH - (SQUASH_WIDTH - 1.0) * left, left, 1.0);
+        sprite.color.set_a(left);
+    }
+}
@@ -1,9 +1,35 @@
 use bevy::prelude::*;
 
+use crate::{
+    plugins::in_game::{
+        level,
+        player::{self, Player, PlayerCount},
+        InGameSet,
+    },
+    state::GameState,
+};
+
+use super::damage::DamageSet;
+
+/// In pixels
+const BAR_POS: Vec2 = Vec2::new(10.0, 10.0);
+/// In pixels
+const BAR_SIZE: Vec2 = Vec2::new(240.0, 24.0);
+/// In pixels
+const BAR_BORDER: f32 = 3.0;
+const BAR_BACKGROUND_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.5);
+const BAR_COLOR: Color = Color::rgb(0.8, 0.15, 0.15);
+
+pub fn health_plugin(app: &mut App) {
+    app.add_event::<Died>()
+        .add_systems(OnEnter(GameState::InGame), create_health_bar)
+        .add_systems(FixedUpdate, check_deaths.after(DamageSet).in_set(InGameSet))
+        .add_systems(Update, update_health_bar.in_set(InGameSet));
+}
+
 #[derive(Component)]
 pub struct Health {
     pub remaining: f32,
-    #[allow(dead_code)]
     pub total: f32,
 }
 
@@ -15,6 +41,11 @@ impl Health {
     pub fn full(total: f32) -> Self {
         Health::new(total, total)
     }
+
+    /// How much health is left, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        (self.remaining / self.total).clamp(0.0, 1.0)
+    }
 }
 
 impl Default for Health {
@@ -22,3 +53,84 @@ impl Default for Health {
         Health::full(100.0)
     }
 }
+
+/// Marks something that has run out of health, so it only dies once.
+#[derive(Component, Debug)]
+pub struct Dead;
+
+/// Sent once when something runs out of health.
+#[derive(Event, Debug)]
+pub struct Died {
+    pub entity: Entity,
+}
+
+pub fn check_deaths(
+    health_query: Query<(Entity, &Health), Without<Dead>>,
+    mut died_events: EventWriter<Died>,
+    mut commands: Commands,
+) {
+    for (entity, health) in &health_query {
+        if health.remaining > 0.0 {
+            continue;
+        }
+
+        debug!("{:?} died", entity);
+        commands.entity(entity).insert(Dead);
+        died_events.send(Died { entity });
+    }
+}
+
+/// The filled part of a player's health bar.
+#[derive(Component)]
+struct HealthBarFill {
+    /// The `Player::index` of the player.
+    player: usize,
+}
+
+fn create_health_bar(mut commands: Commands, player_count: Res<PlayerCount>) {
+    info!("Creating health bars.");
+
+    for index in 0..player_count.0 {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(BAR_POS.y),
+                    right: Val::Px(BAR_POS.x + player::hud_offset(index)),
+                    width: Val::Px(BAR_SIZE.x),
+                    height: Val::Px(BAR_SIZE.y),
+                    padding: UiRect::all(Val::Px(BAR_BORDER)),
+                    ..default()
+                },
+                background_color: BAR_BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .insert(level::LevelEntity)
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: BAR_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(HealthBarFill { player: index });
+            });
+    }
+}
+
+fn update_health_bar(
+    player_query: Query<(&Player, &Health)>,
+    mut fill_query: Query<(&HealthBarFill, &mut Style)>,
+) {
+    for (player, health) in &player_query {
+        for (fill, mut style) in &mut fill_query {
+            if fill.player == player.index {
+                style.width = Val::Percent(health.fraction() * 100.0);
+            }
+        }
+    }
+}
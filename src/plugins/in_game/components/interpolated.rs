@@ -0,0 +1,74 @@
+//! Smooths out movement between physics ticks.
+//!
+//! Physics only moves things once per tick, which doesn't line up with the
+//! frame rate (eg 60 ticks a second on a 144hz monitor). Rendering straight
+//! from the physics position stutters, so instead the `Transform` is drawn
+//! somewhere between the last two ticks' positions. The real position is put
+//! back before the next tick so physics never sees the drawn one.
+
+use bevy::prelude::*;
+
+use crate::plugins::in_game::InGameSet;
+
+pub fn interpolated_plugin(app: &mut App) {
+    app.add_systems(FixedFirst, restore_translation.in_set(InGameSet))
+        .add_systems(FixedLast, record_translation.in_set(InGameSet))
+        .add_systems(
+            Update,
+            interpolate_translation
+                .in_set(InterpolationSet)
+                .in_set(InGameSet),
+        );
+}
+
+/// Runs after the `Transform`s of interpolated entities are updated, anything
+/// that follows them (eg the camera) should run after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InterpolationSet;
+
+/// The entity is moved by physics and should be drawn smoothly.
+#[derive(Component, Debug, Clone)]
+pub struct Interpolated {
+    /// The position after the second to last tick.
+    previous: Vec2,
+    /// The position after the last tick (where physics thinks the entity is).
+    current: Vec2,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec2) -> Self {
+        Interpolated {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+/// Puts entities back where physics left them, so the tick starts from the
+/// real position instead of the drawn one.
+fn restore_translation(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in &mut query {
+        transform.translation.x = interpolated.current.x;
+        transform.translation.y = interpolated.current.y;
+    }
+}
+
+fn record_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.previous = interpolated.current;
+        interpolated.current = transform.translation.truncate();
+    }
+}
+
+fn interpolate_translation(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    // How far into the next tick we are
+    let t = fixed_time.overstep_fraction();
+    for (mut transform, interpolated) in &mut query {
+        let translation = interpolated.previous.lerp(interpolated.current, t);
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::plugins::in_game::InGameSet;
+
+/// How long each blink of a flashing entity lasts, in seconds.
+const FLASH_INTERVAL: f32 = 0.1;
+
+pub fn invulnerable_plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, tick_invulnerability.in_set(InGameSet))
+        .add_systems(Update, (flash, stop_flashing).in_set(InGameSet));
+}
+
+/// The entity can't take damage until this runs out.
+#[derive(Component, Debug, Clone)]
+pub struct Invulnerable {
+    remaining: f32,
+    /// Whether the entity blinks while it is invulnerable.
+    flashing: bool,
+}
+
+impl Invulnerable {
+    pub fn for_seconds(seconds: f32) -> Self {
+        Invulnerable {
+            remaining: seconds,
+            flashing: false,
+        }
+    }
+
+    /// Like `for_seconds`, but the entity blinks so it's obvious that it
+    /// can't be hurt (eg after being hit).
+    pub fn flashing(seconds: f32) -> Self {
+        Invulnerable {
+            remaining: seconds,
+            flashing: true,
+        }
+    }
+
+    /// Combines with `other`, so neither one cuts the other short.
+    fn merge(&mut self, other: Invulnerable) {
+        self.remaining = self.remaining.max(other.remaining);
+        self.flashing |= other.flashing;
+    }
+}
+
+/// Makes `entity` invulnerable, merging with any invulnerability it already
+/// has instead of replacing it.
+///
+/// The merge happens when the command is applied, so two of these in the same
+/// tick don't overwrite each other either.
+pub fn make_invulnerable(commands: &mut Commands, entity: Entity, invulnerable: Invulnerable) {
+    commands.add(move |world: &mut World| {
+        let Some(mut entity) = world.get_entity_mut(entity) else {
+            return;
+        };
+        if let Some(mut existing) = entity.get_mut::<Invulnerable>() {
+            existing.merge(invulnerable);
+        } else {
+            entity.insert(invulnerable);
+        }
+    });
+}
+
+fn tick_invulnerability(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+    mut commands: Commands,
+) {
+    for (entity, mut invulnerable) in &mut query {
+        invulnerable.remaining -= time.delta_seconds();
+        if invulnerable.remaining <= 0.0 {
+            trace!("{:?} is no longer invulnerable", entity);
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn flash(mut query: Query<(&Invulnerable, &mut Visibility)>) {
+    for (invulnerable, mut visibility) in &mut query {
+        if !invulnerable.flashing {
+            // Don't get stuck on a hidden blink
+            visibility.set_if_neq(Visibility::Inherited);
+            continue;
+        }
+
+        let hidden = (invulnerable.remaining / FLASH_INTERVAL) as u32 % 2 == 1;
+        *visibility = if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+/// Makes sure entities are visible again once they stop flashing.
+fn stop_flashing(mut removed: RemovedComponents<Invulnerable>, mut query: Query<&mut Visibility>) {
+    for entity in removed.read() {
+        if let Ok(mut visibility) = query.get_mut(entity) {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
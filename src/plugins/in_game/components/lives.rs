@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugins::in_game::{level, player::Player, InGameSet},
+    state::GameState,
+};
+
+use super::health::{self, Died};
+
+/// In pixels, from the top right corner
+const TEXT_POS: Vec2 = Vec2::new(10.0, 40.0);
+const FONT_SIZE: f32 = 32.0;
+
+pub fn lives_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::InGame), create_text)
+        .add_systems(
+            FixedUpdate,
+            lose_life
+                .run_if(resource_exists::<Lives>)
+                .after(health::check_deaths)
+                .in_set(InGameSet),
+        )
+        .add_systems(
+            Update,
+            update_lives_text
+                .run_if(resource_exists::<Lives>)
+                .in_set(InGameSet),
+        );
+}
+
+/// How many more times the players can die before it's game over.
+///
+/// This is shared by every player, and reset whenever a level is loaded.
+#[derive(Resource, Debug)]
+pub struct Lives {
+    pub remaining: u32,
+}
+
+#[derive(Component)]
+struct LivesText;
+
+/// Respawns every player when any of them dies, or ends the game if they are
+/// out of lives.
+fn lose_life(
+    mut died_events: EventReader<Died>,
+    player_query: Query<(), With<Player>>,
+    mut lives: ResMut<Lives>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Everyone respawns together, so players that die at the same time only
+    // cost one life
+    let deaths = died_events
+        .read()
+        .filter(|Died { entity }| player_query.contains(*entity))
+        .count();
+    if deaths == 0 {
+        return;
+    }
+
+    lives.remaining = lives.remaining.saturating_sub(1);
+    if lives.remaining > 0 {
+        info!("Player died, {} lives left", lives.remaining);
+        next_state.set(GameState::Respawning);
+    } else {
+        info!("Player died with no lives left");
+        next_state.set(GameState::Dead);
+    }
+}
+
+fn create_text(mut commands: Commands) {
+    info!("Creating lives text.");
+
+    commands
+        .spawn(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(TEXT_POS.y),
+                right: Val::Px(TEXT_POS.x),
+                ..default()
+            },
+            text: Text::from_section(
+                "-",
+                TextStyle {
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+            ),
+            ..default()
+        })
+        .insert(LivesText)
+        .insert(level::LevelEntity);
+}
+
+fn update_lives_text(lives: Res<Lives>, mut lives_text: Query<&mut Text, With<LivesText>>) {
+    for mut text in &mut lives_text {
+        text.sections[0].value = format!("Lives: {}", lives.remaining);
+    }
+}
@@ -7,7 +7,7 @@ use crate::plugins::in_game::{
 
 pub fn npc_movement_plugin(app: &mut App) {
     app.add_systems(
-        Update,
+        FixedUpdate,
         (move_enemy, swap_direction, enemy_sprite_flipped).in_set(InGameSet),
     );
 }
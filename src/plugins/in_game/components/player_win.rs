@@ -9,12 +9,15 @@ pub fn player_win_plugin(app: &mut App) {
     app.add_systems(Update, check_win.in_set(InGameSet));
 }
 
+/// The level is won as soon as any player reaches the end.
 fn check_win(
-    player: Query<&Transform, With<Player>>,
+    player_query: Query<&Transform, With<Player>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    let x = player.single().translation.x;
-    if x >= 64.0 * 32.0 {
+    if player_query
+        .iter()
+        .any(|transform| transform.translation.x >= 64.0 * 32.0)
+    {
         next_state.set(GameState::Win);
     }
 }
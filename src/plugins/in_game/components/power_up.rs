@@ -0,0 +1,275 @@
+//! Power-ups are picked up by touching them, and change the player's stats for
+//! a while.
+//!
+//! Every pickup is its own modifier with its own timer, so picking up the same
+//! power-up twice stacks its effect. The stats are recalculated from the
+//! player's original stats every tick (instead of being multiplied and
+//! divided), so they always go back to exactly what they were once every
+//! modifier has run out.
+
+use bevy::prelude::*;
+use bevy_rapier2d::plugin::RapierContext;
+
+use crate::{
+    plugins::in_game::{
+        bundles::power_up::PowerUp,
+        level,
+        player::{self, Player, PlayerCount},
+        InGameSet,
+    },
+    state::GameState,
+};
+
+use super::{character::Character, collect_coin::CoinCollector, damage::DamageIntake};
+
+/// How much faster each speed boost makes the player walk.
+const SPEED_BOOST_MULTIPLIER: f32 = 1.5;
+/// How much higher each high jump makes the player jump.
+const HIGH_JUMP_MULTIPLIER: f32 = 1.5;
+/// How much of the damage the player would take gets through each shield.
+const SHIELD_DAMAGE_MULTIPLIER: f32 = 0.5;
+/// How close coins have to be to be pulled in by the magnet, in pixels.
+const MAGNET_RADIUS: f32 = 240.0;
+/// In pixels, from the top right corner
+const TEXT_POS: Vec2 = Vec2::new(10.0, 80.0);
+const FONT_SIZE: f32 = 24.0;
+
+pub fn power_up_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::InGame), create_text)
+        .add_systems(
+            FixedUpdate,
+            (collect_power_ups, update_power_ups)
+                .chain()
+                .in_set(InGameSet),
+        )
+        .add_systems(Update, update_power_up_text.in_set(InGameSet))
+        .add_systems(OnExit(GameState::InGame), revert_power_ups);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpKind {
+    /// Walk faster.
+    SpeedBoost,
+    /// Jump higher.
+    HighJump,
+    /// Take less damage.
+    Shield,
+    /// Pull in nearby coins.
+    CoinMagnet,
+}
+
+impl PowerUpKind {
+    /// How long the power-up lasts, in seconds.
+    fn duration(&self) -> f32 {
+        match self {
+            PowerUpKind::SpeedBoost => 8.0,
+            PowerUpKind::HighJump => 8.0,
+            PowerUpKind::Shield => 10.0,
+            PowerUpKind::CoinMagnet => 12.0,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::SpeedBoost => "Speed",
+            PowerUpKind::HighJump => "High jump",
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::CoinMagnet => "Magnet",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUpKind::SpeedBoost => Color::rgb(1.0, 0.6, 0.1),
+            PowerUpKind::HighJump => Color::rgb(0.4, 1.0, 0.4),
+            PowerUpKind::Shield => Color::rgb(0.4, 0.6, 1.0),
+            PowerUpKind::CoinMagnet => Color::rgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ActivePowerUp {
+    kind: PowerUpKind,
+    /// In seconds
+    remaining: f32,
+}
+
+/// The stats that power-ups change, from before any were picked up.
+#[derive(Clone, Debug)]
+struct BaseStats {
+    movement_speed: f32,
+    jump_height: f32,
+}
+
+/// The power-ups that are affecting a character.
+#[derive(Component, Debug, Default)]
+pub struct PowerUps {
+    active: Vec<ActivePowerUp>,
+    /// Only set while there are power-ups active.
+    base: Option<BaseStats>,
+}
+
+impl PowerUps {
+    fn count(&self, kind: PowerUpKind) -> i32 {
+        self.active
+            .iter()
+            .filter(|active| active.kind == kind)
+            .count() as i32
+    }
+
+    /// Sets the stats from the original ones and the active power-ups.
+    fn apply(
+        &self,
+        char: &mut Character,
+        damage_intake: &mut DamageIntake,
+        coin_collector: &mut CoinCollector,
+    ) {
+        let Some(base) = &self.base else {
+            return;
+        };
+
+        char.movement_speed =
+            base.movement_speed * SPEED_BOOST_MULTIPLIER.powi(self.count(PowerUpKind::SpeedBoost));
+        char.jump.height =
+            base.jump_height * HIGH_JUMP_MULTIPLIER.powi(self.count(PowerUpKind::HighJump));
+        damage_intake.0 = SHIELD_DAMAGE_MULTIPLIER.powi(self.count(PowerUpKind::Shield));
+        coin_collector.magnet_radius = if self.count(PowerUpKind::CoinMagnet) > 0 {
+            MAGNET_RADIUS
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Lists the power-ups a player has.
+#[derive(Component)]
+struct PowerUpText {
+    /// The `Player::index` of the player.
+    player: usize,
+}
+
+fn collect_power_ups(
+    rapier_context: Res<RapierContext>,
+    mut player_query: Query<(Entity, &mut PowerUps, &Character), With<Player>>,
+    power_up_query: Query<(Entity, &PowerUp)>,
+    mut commands: Commands,
+) {
+    for (player, mut power_ups, char) in &mut player_query {
+        for (entity, power_up) in &power_up_query {
+            if rapier_context.intersection_pair(player, entity) != Some(true) {
+                continue;
+            }
+
+            info!("Picked up {:?} power-up", power_up.0);
+            commands.entity(entity).despawn_recursive();
+            if power_ups.base.is_none() {
+                power_ups.base = Some(BaseStats {
+                    movement_speed: char.movement_speed,
+                    jump_height: char.jump.height,
+                });
+            }
+            power_ups.active.push(ActivePowerUp {
+                kind: power_up.0,
+                remaining: power_up.0.duration(),
+            });
+        }
+    }
+}
+
+fn update_power_ups(
+    time: Res<Time>,
+    mut player_query: Query<(
+        &mut PowerUps,
+        &mut Character,
+        &mut DamageIntake,
+        &mut CoinCollector,
+    )>,
+) {
+    for (mut power_ups, mut char, mut damage_intake, mut coin_collector) in &mut player_query {
+        if power_ups.base.is_none() {
+            continue;
+        }
+
+        power_ups.active.retain_mut(|active| {
+            active.remaining -= time.delta_seconds();
+            if active.remaining <= 0.0 {
+                debug!("{:?} power-up ran out", active.kind);
+            }
+            active.remaining > 0.0
+        });
+
+        power_ups.apply(&mut char, &mut damage_intake, &mut coin_collector);
+        if power_ups.active.is_empty() {
+            power_ups.base = None;
+        }
+    }
+}
+
+/// Takes away every power-up, so none of them carry over to the next level.
+fn revert_power_ups(
+    mut player_query: Query<(
+        &mut PowerUps,
+        &mut Character,
+        &mut DamageIntake,
+        &mut CoinCollector,
+    )>,
+) {
+    for (mut power_ups, mut char, mut damage_intake, mut coin_collector) in &mut player_query {
+        if power_ups.base.is_none() {
+            continue;
+        }
+
+        debug!("Reverting power-ups");
+        power_ups.active.clear();
+        power_ups.apply(&mut char, &mut damage_intake, &mut coin_collector);
+        power_ups.base = None;
+    }
+}
+
+fn create_text(mut commands: Commands, player_count: Res<PlayerCount>) {
+    for index in 0..player_count.0 {
+        commands
+            .spawn(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(TEXT_POS.y),
+                    right: Val::Px(TEXT_POS.x + player::hud_offset(index)),
+                    ..default()
+                },
+                text: Text::default(),
+                ..default()
+            })
+            .insert(PowerUpText { player: index })
+            .insert(level::LevelEntity);
+    }
+}
+
+fn update_power_up_text(
+    player_query: Query<(&Player, &PowerUps)>,
+    mut text_query: Query<(&PowerUpText, &mut Text)>,
+) {
+    for (player, power_ups) in &player_query {
+        for (power_up_text, mut text) in &mut text_query {
+            if power_up_text.player != player.index {
+                continue;
+            }
+
+            text.sections = power_ups
+                .active
+                .iter()
+                .map(|active| {
+                    TextSection::new(
+                        format!("{} {:.1}s\n", active.kind.name(), active.remaining),
+                        TextStyle {
+                            font_size: FONT_SIZE,
+                            color: active.kind.color(),
+                            ..default()
+                        },
+                    )
+                })
+                .collect();
+        }
+    }
+}
@@ -4,31 +4,52 @@ use bevy::prelude::*;
 use bevy_common_assets::json::JsonAssetPlugin;
 
 use crate::{
-    plugins::in_game::bundles::{
-        background::{BackgroundBundle, BackgroundSection, Biome},
-        coin::CoinBundle,
-        enemy::EnemyBundle,
+    plugins::in_game::{
+        assets::{AssetsState, AtlasDescriptors, AtlasLayouts, ImageAssets},
+        bundles::{
+            background::{BackgroundBundle, Biome},
+            checkpoint::CheckpointBundle,
+            coin::CoinBundle,
+            enemy::EnemyBundle,
+            power_up::PowerUpBundle,
+        },
+        components::{
+            character::{Abilities, Character},
+            checkpoint::ActiveCheckpoint,
+            lives::Lives,
+            power_up::PowerUpKind,
+        },
+        player::Player,
     },
     state::GameState,
 };
 
-use self::block::BlockBundle;
+use self::block::{BlockBundle, CrateBundle};
 
 mod block;
+pub mod packs;
 
 const SIZE: f32 = 64.0;
+/// How many lives the player starts a level with, if the level doesn't say.
+const DEFAULT_LIVES: u32 = 3;
+
+pub const LEVEL_PATHS: &[&str] = &["levels/hello_world.level.json"];
 
 pub fn level_plugin(app: &mut App) {
     app.init_state::<LevelState>()
-        .insert_resource(GameAsset::default())
-        .add_plugins(JsonAssetPlugin::<LevelAsset>::new(&["level.json"]))
-        .add_systems(
-            OnEnter(LevelState::LoadingAssets),
-            (load_level_asset, load_image_assets),
-        )
+        .insert_resource(LevelList::built_in())
+        .add_event::<SelectLevel>()
+        .add_plugins((
+            JsonAssetPlugin::<LevelAsset>::new(&["level.json"]),
+            packs::packs_plugin,
+        ))
+        .add_systems(Update, select_level.run_if(on_event::<SelectLevel>()))
+        .add_systems(OnEnter(LevelState::LoadingAssets), load_level_asset)
         .add_systems(
             Update,
-            wait_for_level_load.run_if(in_state(LevelState::LoadingAssets)),
+            wait_for_level_load
+                .run_if(in_state(LevelState::LoadingAssets))
+                .run_if(in_state(AssetsState::Loaded)),
         )
         .add_systems(OnEnter(LevelState::ConstructingLevel), construct_level_res)
         .add_systems(
@@ -39,8 +60,41 @@ pub fn level_plugin(app: &mut App) {
         .add_systems(OnExit(GameState::InGame), reprime_level_state);
 }
 
-fn load_level_asset(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let level_handle = asset_server.load::<LevelAsset>("levels/hello_world.level.json");
+/// Starts loading the selected level (and any textures it overrides), and
+/// starts the game.
+fn select_level(
+    mut commands: Commands,
+    mut events: EventReader<SelectLevel>,
+    asset_server: Res<AssetServer>,
+    mut next_level_state: ResMut<NextState<LevelState>>,
+    mut next_assets_state: ResMut<NextState<AssetsState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(SelectLevel(level)) = events.read().last() else {
+        return;
+    };
+
+    info!("Selected level `{}` ({})", level.name, level.path);
+
+    // Reload the textures, since the last level might have overridden them.
+    // Reloading is cheap since the asset server keeps the loaded ones around.
+    commands.insert_resource(ImageAssets::load(&asset_server, &level.textures));
+    next_assets_state.set(AssetsState::Loading);
+
+    // The player is spawned before the level has loaded, so this can't wait
+    // until the level is constructed
+    commands.remove_resource::<ActiveCheckpoint>();
+    commands.insert_resource(SelectedLevel(level.clone()));
+    next_level_state.set(LevelState::LoadingAssets);
+    next_game_state.set(GameState::InGame);
+}
+
+fn load_level_asset(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selected_level: Res<SelectedLevel>,
+) {
+    let level_handle = asset_server.load::<LevelAsset>(selected_level.0.path.clone());
     let level_handle = LevelHandle(level_handle);
 
     info!("Loading level asset: {:?}", level_handle);
@@ -83,84 +137,62 @@ fn construct_level_res(
 
     info!("Constructing level resource");
 
+    // A new level starts with all of its lives
+    commands.insert_resource(Lives {
+        remaining: level.lives,
+    });
+    commands.insert_resource(LevelBounds(level.bounds));
     commands.insert_resource(level);
     next_state.set(LevelState::WaitingForLevelStart);
 }
 
-fn load_image_assets(asset_server: Res<AssetServer>, mut game_assets: ResMut<GameAsset>) {
-    let mut handles = std::collections::HashMap::from([
-        (
-            ImageHandleId::Enemy,
-            ImageHandles {
-                texture: asset_server.load(crate::plugins::in_game::bundles::enemy::TEXTURE_PATH),
-            },
-        ),
-        (
-            ImageHandleId::Coin,
-            ImageHandles {
-                texture: asset_server.load(crate::plugins::in_game::bundles::coin::TEXTURE_PATH),
-            },
-        ),
-    ]);
-
-    BackgroundSection::enumerate()
-        .iter()
-        .map(|section| {
-            (
-                section.image_handle_id(),
-                ImageHandles {
-                    texture: asset_server.load(section.texture_path()),
-                },
-            )
-        })
-        .for_each(|(k, v)| {
-            handles.insert(k, v);
-        });
-
-    game_assets.image_handles = handles;
-}
-
 fn spawn_blocks(
     mut commands: Commands,
     mut next_state: ResMut<NextState<LevelState>>,
     level: Res<Level>,
-    game_assets: Res<GameAsset>,
-    // FIXME: This is a hack to get the asset server in the system.
-    //        Maybe take out all the preemptive loading and just load on demand? (like the player)
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    image_assets: Res<ImageAssets>,
+    atlas_layouts: Res<AtlasLayouts>,
+    atlas_descriptors: Res<AtlasDescriptors>,
+    mut player_query: Query<&mut Character, With<Player>>,
 ) {
     info!("Spawning blocks for level: {}", level.name);
 
+    if let Some(abilities) = &level.player_abilities {
+        debug!("Level grants player abilities: {:?}", abilities);
+        for mut char in &mut player_query {
+            char.abilities = abilities.clone();
+        }
+    }
+
     for section in level.biome.sections() {
         spawn_entity(
             &mut commands,
-            BackgroundBundle::new(
-                section,
-                game_assets
-                    .image_handles
-                    .get(&section.image_handle_id())
-                    .expect("background assets must be loaded"),
-            ),
+            BackgroundBundle::new(section, image_assets.background(section)),
         );
     }
 
     for block in &level.blocks {
         match block.data {
             BlockData::Dirt => spawn_entity(&mut commands, BlockBundle::new(block.position)),
-            BlockData::Enemy {} => spawn_entity(
+            BlockData::Crate => spawn_entity(&mut commands, CrateBundle::new(block.position)),
+            BlockData::Checkpoint => {
+                spawn_entity(&mut commands, CheckpointBundle::new(block.position))
+            }
+            BlockData::Enemy { heavy } => spawn_entity(
                 &mut commands,
-                EnemyBundle::new(
-                    block.position,
-                    game_assets
-                        .image_handles
-                        .get(&ImageHandleId::Enemy)
-                        .expect("Enemy image assets loaded"),
-                ),
+                EnemyBundle::new(block.position, image_assets.enemy.clone(), heavy),
             ),
+            BlockData::PowerUp { kind } => {
+                spawn_entity(&mut commands, PowerUpBundle::new(block.position, kind))
+            }
             BlockData::Coin => spawn_entity(
                 &mut commands,
-                CoinBundle::new(block.position, &asset_server, &mut texture_atlas_layouts),
+                CoinBundle::new(
+                    block.position,
+                    image_assets.coin.clone(),
+                    atlas_layouts.coin.clone(),
+                    atlas_descriptors.coin.clone(),
+                ),
             ),
         };
     }
@@ -191,7 +223,9 @@ fn reprime_level_state(mut next_state: ResMut<NextState<LevelState>>) {
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, States)]
 enum LevelState {
+    /// No level has been picked yet (on the start screen).
     #[default]
+    NotSelected,
     LoadingAssets,
     ConstructingLevel,
     SpawningBlocks,
@@ -199,29 +233,41 @@ enum LevelState {
     Loaded,
 }
 
-#[derive(/*Component,*/ Resource, Default)]
-struct GameAsset {
-    pub image_handles: HashMap<ImageHandleId, ImageHandles>,
+/// A level that can be picked on the start screen.
+#[derive(Clone, Debug)]
+pub struct LevelEntry {
+    pub name: String,
+    pub path: String,
+    /// Textures to use instead of the built-in ones, keyed by the path of the
+    /// built-in texture.
+    pub textures: HashMap<String, String>,
 }
 
-#[derive(Eq, PartialEq, Hash)]
-pub enum ImageHandleId {
-    Enemy,
-    Coin,
-    BackgroundSwampHills0,
-    BackgroundSwampHills1,
-    BackgroundSwampHills2,
-    BackgroundSwampIsland0,
-    BackgroundSwampIsland1,
-    BackgroundSwampIsland2,
-    BackgroundSwampKelp0,
-    BackgroundSwampKelp1,
-    BackgroundSwampPond,
+/// Every level that can be picked, including ones from level packs.
+#[derive(Resource, Debug)]
+pub struct LevelList(pub Vec<LevelEntry>);
+
+impl LevelList {
+    fn built_in() -> Self {
+        LevelList(
+            LEVEL_PATHS
+                .iter()
+                .map(|path| LevelEntry {
+                    name: packs::level_name(path),
+                    path: path.to_string(),
+                    textures: HashMap::new(),
+                })
+                .collect(),
+        )
+    }
 }
 
-pub struct ImageHandles {
-    pub texture: Handle<Image>,
-}
+/// Send this to pick a level and start the game.
+#[derive(Event)]
+pub struct SelectLevel(pub LevelEntry);
+
+#[derive(Resource)]
+struct SelectedLevel(LevelEntry);
 
 #[derive(Debug, Resource)]
 struct LevelHandle(Handle<LevelAsset>);
@@ -231,8 +277,17 @@ struct Level {
     name: String,
     biome: Biome,
     blocks: Vec<Block>,
+    bounds: Rect,
+    player_abilities: Option<Abilities>,
+    lives: u32,
 }
 
+/// The area of the world that the level takes up, in pixels.
+///
+/// The camera is kept inside this.
+#[derive(Copy, Clone, Debug, Resource)]
+pub struct LevelBounds(pub Rect);
+
 impl From<LevelAsset> for Level {
     fn from(level_asset: LevelAsset) -> Self {
         let blocks = level_asset
@@ -242,12 +297,28 @@ impl From<LevelAsset> for Level {
                 position: block.position * SIZE,
                 ..block
             })
-            .collect();
+            .collect::<Vec<_>>();
+
+        let half_block = Vec2::splat(SIZE / 2.0);
+        let bounds = match level_asset.bounds {
+            Some(bounds) => Rect::from_corners(
+                bounds.min * SIZE - half_block,
+                bounds.max * SIZE + half_block,
+            ),
+            None => blocks
+                .iter()
+                .map(|block| Rect::from_center_half_size(block.position, half_block))
+                .reduce(|bounds, block| bounds.union(block))
+                .unwrap_or_default(),
+        };
 
         Level {
             name: level_asset.name,
             biome: level_asset.biome,
             blocks,
+            bounds,
+            player_abilities: level_asset.player_abilities,
+            lives: level_asset.lives.unwrap_or(DEFAULT_LIVES),
         }
     }
 }
@@ -257,6 +328,23 @@ struct LevelAsset {
     name: String,
     biome: Biome,
     blocks: Vec<Block>,
+    /// Overrides the bounds calculated from the blocks.
+    #[serde(default)]
+    bounds: Option<BoundsAsset>,
+    /// Overrides the player's default abilities.
+    #[serde(default)]
+    player_abilities: Option<Abilities>,
+    /// How many times the player can die before it's game over.
+    #[serde(default)]
+    lives: Option<u32>,
+}
+
+/// The corner blocks of the level (inclusive), in the same units as the
+/// block positions.
+#[derive(Copy, Clone, Debug, serde::Deserialize)]
+struct BoundsAsset {
+    min: Vec2,
+    max: Vec2,
 }
 
 #[derive(Clone, Debug, serde::Deserialize)]
@@ -269,8 +357,20 @@ struct Block {
 #[serde(tag = "type")]
 enum BlockData {
     Dirt,
-    Enemy {},
+    /// Can be pulled around with the grapple.
+    Crate,
+    /// Where the player respawns after dying, once they've touched it.
+    Checkpoint,
+    Enemy {
+        /// Too heavy to be pulled with the grapple.
+        #[serde(default)]
+        heavy: bool,
+    },
     Coin,
+    /// Changes the player's stats for a while once they touch it.
+    PowerUp {
+        kind: PowerUpKind,
+    },
 }
 
 #[derive(Component)]
@@ -1,7 +1,13 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::z_index;
+use crate::{
+    plugins::in_game::components::character::grapple::{pull::Weight, Grappleable},
+    z_index,
+};
+
+const CRATE_SIZE: f32 = 56.0;
+const CRATE_WEIGHT: f32 = 1.5;
 
 #[derive(Component, Copy, Clone, Default, Debug)]
 pub struct Block;
@@ -13,6 +19,7 @@ pub struct BlockBundle {
     collider: Collider,
     friction: Friction,
     block: Block,
+    grappleable: Grappleable,
 }
 
 impl BlockBundle {
@@ -39,6 +46,51 @@ impl BlockBundle {
                 ..default()
             },
             block: Block,
+            grappleable: Grappleable,
+        }
+    }
+}
+
+/// A light box that falls and can be pulled around with the grapple.
+#[derive(Bundle, Clone)]
+pub struct CrateBundle {
+    sprite_bundle: SpriteBundle,
+    rigid_body: RigidBody,
+    collider: Collider,
+    velocity: Velocity,
+    locked_axes: LockedAxes,
+    friction: Friction,
+    grappleable: Grappleable,
+    weight: Weight,
+}
+
+impl CrateBundle {
+    pub fn new(translation: Vec2) -> Self {
+        trace!("Creating crate bundle (translation: {:?})", translation);
+
+        Self {
+            sprite_bundle: SpriteBundle {
+                transform: Transform {
+                    translation: translation.extend(z_index::LEVEL_BASE),
+                    ..default()
+                },
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(CRATE_SIZE)),
+                    color: Color::rgb(0.55, 0.38, 0.2),
+                    ..default()
+                },
+                ..default()
+            },
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::cuboid(CRATE_SIZE / 2.0, CRATE_SIZE / 2.0),
+            velocity: Velocity::default(),
+            locked_axes: LockedAxes::ROTATION_LOCKED,
+            friction: Friction {
+                coefficient: 0.5,
+                ..default()
+            },
+            grappleable: Grappleable,
+            weight: Weight(CRATE_WEIGHT),
         }
     }
 }
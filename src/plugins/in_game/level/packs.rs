@@ -0,0 +1,165 @@
+//! Level packs installed in the user's data directory.
+//!
+//! Each pack is a folder in the packs directory containing a
+//! `manifest.pack.json` file:
+//!
+//! ```json
+//! {
+//!     "name": "My Pack",
+//!     "levels": ["levels/first.level.json", "levels/second.level.json"],
+//!     "textures": { "coin.atlas.png": "textures/gold_coin.png" }
+//! }
+//! ```
+//!
+//! Level and texture paths are relative to the pack's folder. Texture
+//! overrides are keyed by the path of the built-in texture they replace, and
+//! must use the same atlas layout as it.
+//!
+//! Everything is loaded through the `packs://` asset source, so pack levels go
+//! through the same `LevelAsset` pipeline as the built-in ones.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use bevy::{asset::io::AssetSourceBuilder, prelude::*};
+use bevy_common_assets::json::JsonAssetPlugin;
+
+use super::{LevelEntry, LevelList};
+
+/// The name of the asset source that packs are loaded from.
+pub const PACKS_SOURCE: &str = "packs";
+const MANIFEST_FILE: &str = "manifest.pack.json";
+
+/// Registers the `packs://` asset source.
+///
+/// This must be added before `DefaultPlugins`, because asset sources can't be
+/// registered after the `AssetPlugin` is built.
+pub fn packs_source_plugin(app: &mut App) {
+    // Logging isn't set up yet, so any problems are reported when the packs
+    // are loaded instead.
+    let Some(dir) = packs_dir() else {
+        return;
+    };
+
+    app.register_asset_source(
+        PACKS_SOURCE,
+        AssetSourceBuilder::platform_default(&dir.to_string_lossy(), None),
+    );
+}
+
+pub fn packs_plugin(app: &mut App) {
+    app.add_plugins(JsonAssetPlugin::<PackManifest>::new(&["pack.json"]))
+        .init_resource::<PackHandles>()
+        .add_systems(Startup, load_pack_manifests)
+        .add_systems(Update, add_loaded_packs);
+}
+
+/// The directory packs are installed in.
+///
+/// On Linux this follows the XDG base directory spec
+/// (`$XDG_DATA_HOME/pollywog/packs`, falling back to
+/// `~/.local/share/pollywog/packs`).
+pub fn packs_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).map(PathBuf::from);
+
+    let data_dir = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME")
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+    };
+
+    data_dir.map(|dir| dir.join("pollywog").join("packs"))
+}
+
+#[derive(Clone, Debug, serde::Deserialize, Asset, bevy::reflect::TypePath)]
+pub struct PackManifest {
+    name: String,
+    levels: Vec<String>,
+    #[serde(default)]
+    textures: HashMap<String, String>,
+}
+
+/// The manifests that are still loading, with the name of their folder.
+#[derive(Resource, Default)]
+struct PackHandles(Vec<(String, Handle<PackManifest>)>);
+
+fn load_pack_manifests(asset_server: Res<AssetServer>, mut pack_handles: ResMut<PackHandles>) {
+    let Some(dir) = packs_dir() else {
+        warn!("Could not find a data directory, level packs are disabled.");
+        return;
+    };
+    info!("Loading level packs from {:?}", dir);
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        debug!("No level packs installed ({:?} doesn't exist)", dir);
+        return;
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if !entry.path().join(MANIFEST_FILE).is_file() {
+            trace!("Skipping {:?}, it has no pack manifest", entry.path());
+            continue;
+        }
+
+        let folder = entry.file_name().to_string_lossy().into_owned();
+        let handle = asset_server.load(pack_path(&folder, MANIFEST_FILE));
+
+        debug!("Loading level pack manifest for `{}`", folder);
+        pack_handles.0.push((folder, handle));
+    }
+}
+
+fn add_loaded_packs(
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<PackManifest>>,
+    mut pack_handles: ResMut<PackHandles>,
+    mut level_list: ResMut<LevelList>,
+) {
+    pack_handles.0.retain(|(folder, handle)| {
+        if let Some(bevy::asset::LoadState::Failed) = asset_server.get_load_state(handle) {
+            error!("Failed to load the manifest for level pack `{}`", folder);
+            return false;
+        }
+        let Some(manifest) = manifests.get(handle) else {
+            // Still loading
+            return true;
+        };
+
+        info!(
+            "Adding level pack `{}` ({} levels)",
+            manifest.name,
+            manifest.levels.len()
+        );
+
+        let textures = manifest
+            .textures
+            .iter()
+            .map(|(texture, path)| (texture.clone(), pack_path(folder, path)))
+            .collect::<HashMap<_, _>>();
+
+        for level in &manifest.levels {
+            level_list.0.push(LevelEntry {
+                name: format!("{}: {}", manifest.name, level_name(level)),
+                path: pack_path(folder, level),
+                textures: textures.clone(),
+            });
+        }
+
+        false
+    });
+}
+
+fn pack_path(folder: &str, path: &str) -> String {
+    format!("{}://{}/{}", PACKS_SOURCE, folder, path)
+}
+
+/// A human-readable name for a level, based on its file name.
+pub fn level_name(path: &str) -> String {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let stem = file_name.trim_end_matches(".level.json");
+
+    stem.replace('_', " ")
+}
@@ -1,8 +1,22 @@
 use crate::{
+    plugins::in_game::assets::{AtlasDescriptors, AtlasLayouts, ImageAssets},
     plugins::in_game::components::{
-        animated_sprite::{AnimatedSprite, AnimationIndices, AnimationTimer},
-        character::{jump::JumpComponent, Action, Character},
+        animated_sprite::{AnimatedSprite, CurrentlyAnimating, SpriteAnimation},
+        character::{
+            attack::AttackState,
+            dash::DashState,
+            grapple::{GrappleBundle, GrappleMode, GrappleSettings},
+            input::LatchedPresses,
+            jump::{JumpAssist, JumpSettings},
+            velocity::{KinematicVelocity, MovementSettings},
+            wall::WallContact,
+            Abilities, Action, Character,
+        },
+        checkpoint::ActiveCheckpoint,
         collect_coin::CoinCollector,
+        damage::DamageIntake,
+        interpolated::Interpolated,
+        power_up::PowerUps,
     },
     plugins::in_game::level,
     state::GameState,
@@ -12,27 +26,104 @@ use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_rapier2d::prelude::*;
 use leafwing_input_manager::prelude::*;
 
-use crate::{plugins::in_game::components::health::Health, GRAVITY};
+use crate::plugins::in_game::components::health::Health;
 
 /// In pixels
 const TEXTURE_SIZE: Vec2 = Vec2::new(233.0, 373.0);
-const TEXTURE_PATH: &str = "player_new.atlas.png";
+/// How wide the player (and their collider) is in the world, in pixels.
+const WIDTH: f32 = 64.0;
+pub const TEXTURE_PATH: &str = "player_new.atlas.png";
+pub const ATLAS_PATH: &str = "player_new.atlas.json";
 
-/// In meters
-const JUMP_HEIGHT: f32 = 1.5;
+/// In pixels
+const JUMP_HEIGHT: f32 = 90.0;
 /// In seconds
 const JUMP_TIME_TO_PEAK: f32 = 0.5;
+const JUMP_RELEASE_GRAVITY_MULTIPLIER: f32 = 3.0;
+/// In seconds
+const JUMP_COYOTE_TIME: f32 = 0.1;
+/// In seconds
+const JUMP_BUFFER_TIME: f32 = 0.1;
 
-const MOVEMENT_SPEED: f32 = 3.0;
+/// In pixels per second
+const MOVEMENT_SPEED: f32 = 180.0;
+/// In pixels per second, per second
+const GROUND_ACCELERATION: f32 = 1800.0;
+/// In pixels per second, per second
+const AIR_ACCELERATION: f32 = 900.0;
+/// In pixels per second, per second
+const GROUND_FRICTION: f32 = 2400.0;
+/// In pixels per second, per second
+const AIR_FRICTION: f32 = 300.0;
+
+/// In pixels
+const GRAPPLE_RANGE: f32 = 500.0;
+/// In seconds
+const GRAPPLE_COOLDOWN: f32 = 0.4;
+const GRAPPLE_MAX_PULL_WEIGHT: f32 = 2.0;
 
 pub const INITIAL_HEALTH: f32 = 100.0;
+/// How far above a checkpoint the player respawns, so they don't start inside
+/// the ground, in pixels.
+const CHECKPOINT_SPAWN_HEIGHT: f32 = 64.0;
+/// The gap between players when they spawn, so their colliders don't start
+/// out overlapping, in pixels.
+const SPAWN_GAP: f32 = 16.0;
+
+/// The most people that can play at once.
+pub const MAX_PLAYERS: usize = 4;
+/// Tints each player, so they can be told apart.
+const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::rgb(0.6, 0.8, 1.0),
+    Color::rgb(1.0, 0.7, 0.7),
+    Color::rgb(0.7, 1.0, 0.6),
+];
+/// How far apart each player's part of the HUD is, in pixels.
+const HUD_COLUMN_WIDTH: f32 = 260.0;
+
+mod animation;
 
 pub fn player_plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::InGame), spawn);
+    app.add_plugins(animation::animation_plugin)
+        .init_resource::<PlayerCount>()
+        .add_systems(OnEnter(GameState::InGame), spawn);
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Player {
+    /// Which player this is, starting from 0.
+    pub index: usize,
+}
+
+impl Player {
+    /// The first player always has the keyboard (or the left half of it), so
+    /// they're the only one that can aim with the mouse.
+    pub fn uses_mouse(&self) -> bool {
+        self.index == 0
+    }
 }
 
-#[derive(Component, Default)]
-pub struct Player;
+/// How many people are playing, chosen on the start screen.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        PlayerCount(1)
+    }
+}
+
+/// The color that the player at `index` is tinted.
+pub fn color(index: usize) -> Color {
+    PLAYER_COLORS[index % MAX_PLAYERS]
+}
+
+/// How far the HUD for the player at `index` is from the edge of the screen,
+/// in pixels.
+pub fn hud_offset(index: usize) -> f32 {
+    index as f32 * HUD_COLUMN_WIDTH
+}
 
 #[derive(Bundle)]
 struct PlayerBundle {
@@ -41,114 +132,241 @@ struct PlayerBundle {
 
     // Sprite
     animation: AnimatedSprite,
+    player_animation: animation::PlayerAnimation,
+    currently_animating: CurrentlyAnimating,
     sprite_bundle: SpriteBundle,
 
     // Physics
     character_controller: KinematicCharacterController,
+    velocity: KinematicVelocity,
+    interpolated: Interpolated,
     collider: Collider,
     rigid_body: RigidBody,
 
     // Properties
     char: Character,
+    jump_assist: JumpAssist,
+    wall_contact: WallContact,
+    dash_state: DashState,
+    attack_state: AttackState,
+    grapple: GrappleBundle,
     coins: CoinCollector,
     health: Health,
+    damage_intake: DamageIntake,
+    power_ups: PowerUps,
 
     // Input manager
     input_manager: InputManagerBundle<Action>,
+    latched_presses: LatchedPresses,
 }
 
 impl PlayerBundle {
     fn new(
-        asset_server: Res<AssetServer>,
-        mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        window: &Window,
+        image_assets: &ImageAssets,
+        atlas_layouts: &AtlasLayouts,
+        atlas_descriptors: &AtlasDescriptors,
+        translation: Vec2,
+        index: usize,
+        player_count: usize,
+        gamepads: &[Gamepad],
     ) -> Self {
-        debug!("Creating player bundle");
-
-        let layout = TextureAtlasLayout::from_grid(TEXTURE_SIZE, 5, 2, None, None);
-        let layout = texture_atlas_layouts.add(layout);
-
-        let texture = asset_server.load(TEXTURE_PATH);
-        let animation_indices = AnimationIndices { first: 0, last: 8 };
+        debug!("Creating player bundle for player {}", index + 1);
 
         Self {
-            player: Player,
+            player: Player { index },
 
             animation: AnimatedSprite {
                 texture_atlas: TextureAtlas {
-                    layout,
-                    index: animation_indices.first,
+                    layout: atlas_layouts.player.clone(),
+                    index: 0,
                 },
-                animation_indices,
-                animation_timer: AnimationTimer(Timer::from_seconds(0.05, TimerMode::Repeating)),
+                animation: SpriteAnimation::new(atlas_descriptors.player.clone()),
             },
+            player_animation: animation::PlayerAnimation::new(INITIAL_HEALTH, color(index)),
+            currently_animating: CurrentlyAnimating,
             sprite_bundle: SpriteBundle {
                 transform: Transform {
-                    translation: Vec3::new(0.0, window.height(), z_index::LEVEL_BASE),
-                    scale: Vec3::splat(64.0 / TEXTURE_SIZE.x),
+                    translation: translation.extend(z_index::LEVEL_BASE),
+                    scale: Vec3::splat(WIDTH / TEXTURE_SIZE.x),
                     ..default()
                 },
-                texture,
+                texture: image_assets.player.clone(),
                 sprite: Sprite {
                     custom_size: Some(TEXTURE_SIZE),
+                    color: color(index),
                     ..default()
                 },
                 ..default()
             },
 
-            character_controller: KinematicCharacterController {
-                translation: Some(GRAVITY),
-                ..default()
-            },
+            character_controller: KinematicCharacterController::default(),
+            velocity: KinematicVelocity::default(),
+            interpolated: Interpolated::new(translation),
+            // Scaled down to `WIDTH` along with the sprite
             collider: Collider::cuboid(TEXTURE_SIZE.x / 2.0, TEXTURE_SIZE.y / 2.0),
             rigid_body: RigidBody::KinematicPositionBased,
 
             char: Character {
                 movement_speed: MOVEMENT_SPEED,
+                movement: MovementSettings {
+                    ground_acceleration: GROUND_ACCELERATION,
+                    air_acceleration: AIR_ACCELERATION,
+                    ground_friction: GROUND_FRICTION,
+                    air_friction: AIR_FRICTION,
+                },
+                jump: JumpSettings {
+                    height: JUMP_HEIGHT,
+                    time_to_peak: JUMP_TIME_TO_PEAK,
+                    release_gravity_multiplier: JUMP_RELEASE_GRAVITY_MULTIPLIER,
+                    coyote_time: JUMP_COYOTE_TIME,
+                    buffer_time: JUMP_BUFFER_TIME,
+                },
+                grapple: GrappleSettings {
+                    range: GRAPPLE_RANGE,
+                    cooldown: GRAPPLE_COOLDOWN,
+                    max_pull_weight: GRAPPLE_MAX_PULL_WEIGHT,
+                },
+                abilities: Abilities {
+                    wall_slide: true,
+                    wall_jump: true,
+                    air_jumps: 0,
+                    grapple_mode: GrappleMode::Pull,
+                },
             },
+            jump_assist: JumpAssist::default(),
+            wall_contact: WallContact::default(),
+            dash_state: DashState::default(),
+            attack_state: AttackState::default(),
+            grapple: GrappleBundle::default(),
             coins: CoinCollector::default(),
             health: Health::full(INITIAL_HEALTH),
+            damage_intake: DamageIntake::default(),
+            power_ups: PowerUps::default(),
 
             input_manager: InputManagerBundle::<Action> {
                 action_state: ActionState::default(),
-                input_map: get_input_map(),
+                input_map: get_input_map(index, player_count, gamepads),
             },
+            latched_presses: LatchedPresses::default(),
         }
     }
 }
 
-fn get_input_map() -> InputMap<Action> {
+/// The first player gets the keyboard and mouse, and everyone else gets one of
+/// the connected `gamepads` each. If there aren't enough gamepads to go
+/// around, the second player gets the right half of the keyboard instead.
+/// Playing alone, any gamepad can be used as well.
+fn get_input_map(index: usize, player_count: usize, gamepads: &[Gamepad]) -> InputMap<Action> {
     let mut input_map = InputMap::default();
 
+    let split_keyboard = gamepads.len() + 1 < player_count;
+    let keyboard_players = if split_keyboard { 2 } else { 1 };
+
+    if index == 0 {
+        insert_left_keyboard_bindings(&mut input_map);
+        if !split_keyboard {
+            insert_right_keyboard_bindings(&mut input_map);
+        }
+        if player_count == 1 {
+            insert_gamepad_bindings(&mut input_map);
+        }
+    } else if index < keyboard_players {
+        insert_right_keyboard_bindings(&mut input_map);
+    } else if let Some(&gamepad) = gamepads.get(index - keyboard_players) {
+        insert_gamepad_bindings(&mut input_map);
+        input_map.set_gamepad(gamepad);
+    } else {
+        warn!("No gamepad connected for player {}", index + 1);
+    }
+
+    input_map
+}
+
+/// WASD, with the mouse for aiming.
+fn insert_left_keyboard_bindings(input_map: &mut InputMap<Action>) {
     input_map
-        .insert(Action::Left, KeyCode::ArrowLeft)
         .insert(Action::Left, KeyCode::KeyA)
-        .insert(Action::Right, KeyCode::ArrowRight)
         .insert(Action::Right, KeyCode::KeyD)
-        .insert(Action::Jump, KeyCode::ArrowUp)
         .insert(Action::Jump, KeyCode::KeyW)
         .insert(Action::Jump, KeyCode::Space)
         .insert(Action::Grapple, KeyCode::KeyE)
-        .insert(Action::Grapple, KeyCode::Slash);
+        .insert(Action::Dash, KeyCode::ShiftLeft)
+        .insert(Action::Attack, KeyCode::KeyF)
+        .insert(Action::ReelIn, KeyCode::KeyQ)
+        .insert(Action::ReelIn, MouseWheelDirection::Up)
+        .insert(Action::ReelOut, KeyCode::KeyS)
+        .insert(Action::ReelOut, MouseWheelDirection::Down);
+}
+
+/// The arrow keys, with IJKL for aiming.
+fn insert_right_keyboard_bindings(input_map: &mut InputMap<Action>) {
+    input_map
+        .insert(Action::Left, KeyCode::ArrowLeft)
+        .insert(Action::Right, KeyCode::ArrowRight)
+        .insert(Action::Jump, KeyCode::ArrowUp)
+        .insert(Action::Grapple, KeyCode::Slash)
+        .insert(Action::Dash, KeyCode::ShiftRight)
+        .insert(Action::Attack, KeyCode::Period)
+        .insert(Action::ReelIn, KeyCode::Comma)
+        .insert(Action::ReelOut, KeyCode::ArrowDown)
+        // 8-way aiming for keyboards, diagonals come from pressing two keys
+        .insert(
+            Action::Aim,
+            VirtualDPad {
+                up: KeyCode::KeyI.into(),
+                down: KeyCode::KeyK.into(),
+                left: KeyCode::KeyJ.into(),
+                right: KeyCode::KeyL.into(),
+            },
+        );
+}
 
+fn insert_gamepad_bindings(input_map: &mut InputMap<Action>) {
     input_map
+        .insert(Action::Left, GamepadButtonType::DPadLeft)
+        .insert(Action::Right, GamepadButtonType::DPadRight)
+        .insert(Action::Jump, GamepadButtonType::South)
+        .insert(Action::Grapple, GamepadButtonType::RightTrigger2)
+        .insert(Action::Dash, GamepadButtonType::East)
+        .insert(Action::Attack, GamepadButtonType::West)
+        .insert(Action::ReelIn, GamepadButtonType::RightTrigger)
+        .insert(Action::ReelOut, GamepadButtonType::LeftTrigger)
+        .insert(Action::Aim, DualAxis::right_stick());
 }
 
 fn spawn(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    image_assets: Res<ImageAssets>,
+    atlas_layouts: Res<AtlasLayouts>,
+    atlas_descriptors: Res<AtlasDescriptors>,
+    active_checkpoint: Option<Res<ActiveCheckpoint>>,
+    player_count: Res<PlayerCount>,
+    gamepads: Res<Gamepads>,
     window_query: Query<&Window, With<PrimaryWindow>>,
 ) {
-    let window = window_query.single();
+    let start = match active_checkpoint {
+        Some(checkpoint) => checkpoint.0 + Vec2::new(0.0, CHECKPOINT_SPAWN_HEIGHT),
+        None => Vec2::new(0.0, window_query.single().height()),
+    };
 
-    debug!("Spawning player");
-    level::spawn_entity(
-        &mut commands,
-        PlayerBundle::new(asset_server, texture_atlas_layouts, window),
-    );
-}
+    // Gamepad ids aren't reused after reconnecting, so they can have gaps
+    let mut gamepads = gamepads.iter().collect::<Vec<_>>();
+    gamepads.sort_by_key(|gamepad| gamepad.id);
 
-pub fn jump_component() -> JumpComponent {
-    JumpComponent::new(JUMP_HEIGHT, JUMP_TIME_TO_PEAK)
+    for index in 0..player_count.0 {
+        let translation = start + Vec2::new(index as f32 * (WIDTH + SPAWN_GAP), 0.0);
+        debug!("Spawning player {} at {:?}", index + 1, translation);
+        level::spawn_entity(
+            &mut commands,
+            PlayerBundle::new(
+                &image_assets,
+                &atlas_layouts,
+                &atlas_descriptors,
+                translation,
+                index,
+                player_count.0,
+                &gamepads,
+            ),
+        );
+    }
 }
@@ -0,0 +1,167 @@
+//! Picks which animation the player plays from what they are doing.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::KinematicCharacterControllerOutput;
+
+use crate::{
+    plugins::in_game::{
+        components::{
+            animated_sprite::SpriteAnimation,
+            character::{grapple::GrappleState, velocity::KinematicVelocity},
+            health::Health,
+        },
+        InGameSet,
+    },
+    state::GameState,
+};
+
+use super::Player;
+
+/// How long the hurt clip plays for after taking damage, in seconds.
+const HURT_TIME: f32 = 0.3;
+/// How fast the player has to be moving along the ground to count as running,
+/// in pixels per second.
+const MIN_RUN_SPEED: f32 = 10.0;
+
+pub fn animation_plugin(app: &mut App) {
+    app.add_systems(Update, choose_clip.in_set(InGameSet))
+        // The game stays visible behind the dead screen, so the death clip
+        // plays there
+        .add_systems(OnEnter(GameState::Dead), play_death_clip);
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+enum PlayerClip {
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Grapple,
+    Hurt,
+    Death,
+}
+
+impl PlayerClip {
+    /// The name of the clip in the player's atlas descriptor.
+    fn name(self) -> &'static str {
+        match self {
+            PlayerClip::Idle => "idle",
+            PlayerClip::Run => "run",
+            PlayerClip::Jump => "jump",
+            PlayerClip::Fall => "fall",
+            PlayerClip::Grapple => "grapple",
+            PlayerClip::Hurt => "hurt",
+            PlayerClip::Death => "death",
+        }
+    }
+
+    /// Makes clips that share frames still look different. Other clips use
+    /// the player's `tint`.
+    fn color(self, tint: Color) -> Color {
+        match self {
+            PlayerClip::Hurt => Color::rgb(1.0, 0.4, 0.4),
+            PlayerClip::Death => Color::GRAY,
+            _ => tint,
+        }
+    }
+}
+
+/// Which clip the player is playing.
+#[derive(Component, Debug, Clone)]
+pub struct PlayerAnimation {
+    clip: PlayerClip,
+    /// Used to tell when the player takes damage.
+    last_health: f32,
+    hurt_remaining: f32,
+    /// Tells players apart.
+    tint: Color,
+}
+
+impl PlayerAnimation {
+    pub fn new(health: f32, tint: Color) -> Self {
+        PlayerAnimation {
+            clip: PlayerClip::default(),
+            last_health: health,
+            hurt_remaining: 0.0,
+            tint,
+        }
+    }
+
+    /// Switches to `clip`, starting it from the beginning.
+    fn set_clip(&mut self, clip: PlayerClip, animation: &mut SpriteAnimation, sprite: &mut Sprite) {
+        if self.clip == clip {
+            return;
+        }
+
+        trace!("Player animation {:?} -> {:?}", self.clip, clip);
+        self.clip = clip;
+        animation.play(clip.name());
+        sprite.color = clip.color(self.tint);
+    }
+}
+
+// LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
+#[allow(clippy::type_complexity)]
+fn choose_clip(
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            &mut PlayerAnimation,
+            &mut SpriteAnimation,
+            &mut Sprite,
+            &Health,
+            &KinematicVelocity,
+            &GrappleState,
+            Option<&KinematicCharacterControllerOutput>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (
+        mut player_animation,
+        mut animation,
+        mut sprite,
+        health,
+        velocity,
+        grapple_state,
+        output,
+    ) in &mut player_query
+    {
+        if health.remaining < player_animation.last_health {
+            player_animation.hurt_remaining = HURT_TIME;
+        } else {
+            player_animation.hurt_remaining =
+                (player_animation.hurt_remaining - time.delta_seconds()).max(0.0);
+        }
+        player_animation.last_health = health.remaining;
+
+        let grounded = output.is_some_and(|output| output.grounded);
+        let clip = if player_animation.hurt_remaining > 0.0 {
+            PlayerClip::Hurt
+        } else if *grapple_state == GrappleState::Grappling {
+            PlayerClip::Grapple
+        } else if !grounded && velocity.y > 0.0 {
+            PlayerClip::Jump
+        } else if !grounded {
+            PlayerClip::Fall
+        } else if velocity.x.abs() >= MIN_RUN_SPEED {
+            PlayerClip::Run
+        } else {
+            PlayerClip::Idle
+        };
+
+        player_animation.set_clip(clip, &mut animation, &mut sprite);
+    }
+}
+
+fn play_death_clip(
+    mut player_query: Query<
+        (&mut PlayerAnimation, &mut SpriteAnimation, &mut Sprite),
+        With<Player>,
+    >,
+) {
+    for (mut player_animation, mut animation, mut sprite) in &mut player_query {
+        player_animation.set_clip(PlayerClip::Death, &mut animation, &mut sprite);
+    }
+}
@@ -1,5 +1,10 @@
 use bevy::prelude::*;
 
+use crate::plugins::in_game::{
+    assets::AssetsState,
+    level::{LevelList, SelectLevel},
+    player::{PlayerCount, MAX_PLAYERS},
+};
 use crate::state::GameState;
 use crate::BACKGROUND_COLOR;
 
@@ -24,37 +29,105 @@ const BUTTON_BG_NORMAL: BackgroundColor = BackgroundColor(Color::Hsla {
 
 pub fn start_screen_plugin(app: &mut App) {
     app.add_systems(OnEnter(GameState::StartScreen), setup)
-        .add_systems(Update, (button_interaction_style, start_button_pressed))
+        .add_systems(
+            Update,
+            (
+                button_interaction_style,
+                player_count_button_pressed,
+                update_player_count_text
+                    .run_if(in_state(GameState::StartScreen))
+                    .run_if(resource_changed::<PlayerCount>),
+                // Don't let the game start until everything it needs is loaded
+                start_button_pressed.run_if(in_state(AssetsState::Loaded)),
+                // Level packs finish loading after the start screen is shown
+                refresh_start_buttons
+                    .run_if(in_state(GameState::StartScreen))
+                    .run_if(resource_changed::<LevelList>),
+            ),
+        )
         .add_systems(OnExit(GameState::StartScreen), cleanup);
 }
 
-fn setup(mut commands: Commands) {
+fn setup(mut commands: Commands, level_list: Res<LevelList>, player_count: Res<PlayerCount>) {
     // root node
-    commands
+    let root_node = commands
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
                 ..default()
             },
             background_color: BackgroundColor(BACKGROUND_COLOR),
             ..default()
         })
         .insert(RootNode)
-        .with_children(|parent| {
+        .id();
+
+    spawn_player_count_button(&mut commands, root_node, &player_count);
+    spawn_start_buttons(&mut commands, root_node, &level_list);
+}
+
+/// Adds the button that picks how many people are playing to the root node.
+fn spawn_player_count_button(
+    commands: &mut Commands,
+    root_node: Entity,
+    player_count: &PlayerCount,
+) {
+    commands.entity(root_node).with_children(|parent| {
+        parent
+            .spawn(ButtonBundle {
+                style: Style {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::WHITE),
+                ..default()
+            })
+            .insert(PlayerCountButton)
+            .with_children(|parent| {
+                parent
+                    .spawn(TextBundle {
+                        text: Text::from_section(
+                            player_count_label(player_count),
+                            TextStyle {
+                                font_size: 32.0,
+                                color: Color::BLACK,
+                                ..default()
+                            },
+                        ),
+                        ..default()
+                    })
+                    .insert(PlayerCountText);
+            });
+    });
+}
+
+fn player_count_label(player_count: &PlayerCount) -> String {
+    match player_count.0 {
+        1 => "1 player".to_string(),
+        count => format!("{} players", count),
+    }
+}
+
+/// Adds a start button for every level to the root node.
+fn spawn_start_buttons(commands: &mut Commands, root_node: Entity, level_list: &LevelList) {
+    commands.entity(root_node).with_children(|parent| {
+        for (index, level) in level_list.0.iter().enumerate() {
             parent
                 .spawn(ButtonBundle {
                     style: Style { ..default() },
                     background_color: BackgroundColor(Color::WHITE),
                     ..default()
                 })
-                .insert(StartButton)
+                .insert(StartButton(index))
                 .with_children(|parent| {
                     parent.spawn(TextBundle {
                         text: Text::from_section(
-                            "Start",
+                            level.name.clone(),
                             TextStyle {
                                 font_size: 42.0,
                                 color: Color::BLACK,
@@ -64,7 +137,24 @@ fn setup(mut commands: Commands) {
                         ..default()
                     });
                 });
-        });
+        }
+    });
+}
+
+fn refresh_start_buttons(
+    mut commands: Commands,
+    root_node: Query<Entity, With<RootNode>>,
+    level_list: Res<LevelList>,
+    player_count: Res<PlayerCount>,
+) {
+    let Ok(root_node) = root_node.get_single() else {
+        return;
+    };
+
+    debug!("Level list changed, refreshing start buttons");
+    commands.entity(root_node).despawn_descendants();
+    spawn_player_count_button(&mut commands, root_node, &player_count);
+    spawn_start_buttons(&mut commands, root_node, &level_list);
 }
 
 // LINT ALLOW: Ok because it's a bevy Query, and not actually very complex
@@ -90,14 +180,37 @@ fn button_interaction_style(
     }
 }
 
-fn start_button_pressed(
-    interactions: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
-    mut next_state: ResMut<NextState<GameState>>,
+/// Cycles through how many people are playing.
+fn player_count_button_pressed(
+    interactions: Query<&Interaction, (Changed<Interaction>, With<PlayerCountButton>)>,
+    mut player_count: ResMut<PlayerCount>,
 ) {
     for interaction in &interactions {
         if interaction == &Interaction::Pressed {
-            debug!("Start button pressed");
-            next_state.set(GameState::InGame);
+            player_count.0 = player_count.0 % MAX_PLAYERS + 1;
+            debug!("Player count changed to {}", player_count.0);
+        }
+    }
+}
+
+fn update_player_count_text(
+    player_count: Res<PlayerCount>,
+    mut text_query: Query<&mut Text, With<PlayerCountText>>,
+) {
+    for mut text in &mut text_query {
+        text.sections[0].value = player_count_label(&player_count);
+    }
+}
+
+fn start_button_pressed(
+    interactions: Query<(&Interaction, &StartButton), Changed<Interaction>>,
+    level_list: Res<LevelList>,
+    mut select_level: EventWriter<SelectLevel>,
+) {
+    for (interaction, StartButton(index)) in &interactions {
+        if interaction == &Interaction::Pressed {
+            debug!("Start button pressed for level {}", index);
+            select_level.send(SelectLevel(level_list.0[*index].clone()));
         }
     }
 }
@@ -107,8 +220,16 @@ fn cleanup(mut commands: Commands, root_node: Query<Entity, With<RootNode>>) {
     commands.entity(root_node).despawn_recursive();
 }
 
+/// Starts the level at this index in the `LevelList`.
 #[derive(Component)]
-struct StartButton;
+struct StartButton(usize);
 
 #[derive(Component)]
 struct RootNode;
+
+/// Changes the `PlayerCount` when pressed.
+#[derive(Component)]
+struct PlayerCountButton;
+
+#[derive(Component)]
+struct PlayerCountText;
@@ -6,5 +6,9 @@ pub enum GameState {
     StartScreen,
     InGame,
     Win,
+    /// The player died but still has lives left, so the level is rebuilt
+    /// (from the last checkpoint) without showing the dead screen.
+    Respawning,
+    /// The player ran out of lives.
     Dead,
 }